@@ -1,36 +1,43 @@
-use crate::math::vector::Vector;
-use crate::world::rigid_box::RigidBox;
-use crate::world::world::World;
 use kiss3d::camera::ArcBall;
 use kiss3d::light::Light;
 use kiss3d::window::Window;
-use math::quaternion::Quaternion;
-use nalgebra::{Isometry3, Point3, Translation3, UnitQuaternion, Vector3};
+use nalgebra::{Point3, Translation3, Vector3};
+use physics_engine::math::quaternion::Quaternion;
+use physics_engine::math::vector::Vector;
+use physics_engine::world::rigid_box::RigidBox;
+use physics_engine::world::static_collider::StaticCollider;
+use physics_engine::world::world::World;
+use std::time::Instant;
+use viewer::Viewer;
 
-mod math;
-mod world;
+mod viewer;
 
-const RECT_X: f32 = 4.0;
-const RECT_Y: f32 = 2.0;
-const RECT_Z: f32 = 3.0;
+const RECT_X: f64 = 4.0;
+const RECT_Y: f64 = 2.0;
+const RECT_Z: f64 = 3.0;
 
 fn main() {
     let mut world = World::new(Vector::new(0.0, 0.0, -1.0));
-    let rbox = RigidBox::new(RECT_X as f64, RECT_Y as f64, RECT_Z as f64, 1.0);
+    let rbox = RigidBox::new(RECT_X, RECT_Y, RECT_Z, 1.0);
 
     world.add(
-        0,
         &rbox,
         &Vector::new(-10.0, 0.0, 10.0),
-        &math::quaternion::Quaternion::from_rotation(&Vector::new(0.0, 1.0, 0.0), 0.0),
+        &Quaternion::from_rotation(&Vector::new(0.0, 1.0, 0.0), 0.0),
         &Vector::new(10.0, 0.0, 0.0),
         &Vector::new(1.0, 10.0, 4.0),
     );
+    world.add(
+        &RigidBox::new(1.0, 1.0, 1.0, 1.0),
+        &Vector::new(0.0, 0.0, 8.0),
+        &Quaternion::from_rotation(&Vector::new(1.0, 0.0, 0.0), 0.5),
+        &Vector::zero(),
+        &Vector::new(0.0, 0.2, 0.0),
+    );
+    world.add_static(StaticCollider::plane(&Vector::new(0.0, 0.0, 1.0), 0.0));
 
     let mut window = Window::new("Physics Engine");
-
-    let mut c = window.add_cube(RECT_X, RECT_Y, RECT_Z);
-    c.set_color(1.0, 1.0, 1.0);
+    let mut viewer = Viewer::default();
 
     let mut floor = window.add_cube(100.0, 100.0, 1.0);
     floor.append_translation(&Translation3::new(0.0, 0.0, -0.5));
@@ -53,18 +60,12 @@ fn main() {
     let mut camera = ArcBall::new(Point3::new(4.0, -30.0, 4.0), Point3::origin());
     camera.set_up_axis(Vector3::z());
 
-    let mut t = 0.0;
+    let mut last_frame = Instant::now();
 
     while window.render_with_camera(&mut camera) {
-        t = world.step(t, 0.05);
-        world.for_each_object(|_object_id: usize, p: &Vector, q: &Quaternion| {
-            c.set_local_translation(Translation3::new(p.x as f32, p.y as f32, p.z as f32));
-            c.set_local_rotation(UnitQuaternion::from_quaternion(nalgebra::Quaternion::new(
-                q.w as f32,
-                q.v.x as f32,
-                q.v.y as f32,
-                q.v.z as f32,
-            )));
-        })
+        let now = Instant::now();
+        world.advance(now.duration_since(last_frame).as_secs_f64());
+        last_frame = now;
+        viewer.sync(&mut window, &world);
     }
 }
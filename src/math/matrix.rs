@@ -1,8 +1,10 @@
 use crate::math::approx_eq::{ApproxEq, EPSILON};
+use crate::math::quaternion::Quaternion;
+use crate::math::sq;
 use crate::math::vector::Vector;
-use std::ops::Mul;
+use std::ops::{Add, Mul, Sub};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Matrix {
     // row-major elements
     elems: [f64; 9],
@@ -12,6 +14,32 @@ impl Matrix {
     pub const fn new(elems: [f64; 9]) -> Self {
         Self { elems }
     }
+    pub const fn identity() -> Self {
+        Self::new([1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0])
+    }
+    pub const fn diagonal(x: f64, y: f64, z: f64) -> Self {
+        Self::new([x, 0.0, 0.0, 0.0, y, 0.0, 0.0, 0.0, z])
+    }
+    // a b^T
+    pub fn outer(a: &Vector, b: &Vector) -> Self {
+        Self::new([
+            a.x * b.x,
+            a.x * b.y,
+            a.x * b.z,
+            a.y * b.x,
+            a.y * b.y,
+            a.y * b.z,
+            a.z * b.x,
+            a.z * b.y,
+            a.z * b.z,
+        ])
+    }
+    pub fn trace(&self) -> f64 {
+        self.elems[0] + self.elems[4] + self.elems[8]
+    }
+    pub fn column(&self, j: usize) -> Vector {
+        Vector::new(self.elems[j], self.elems[3 + j], self.elems[6 + j])
+    }
     pub fn transpose(&self) -> Self {
         Self {
             elems: [
@@ -28,31 +56,67 @@ impl Matrix {
         }
     }
     // Assumes `self` is orthonormal
-    #[cfg(feature = "matrixtoquat")]
-    pub fn to_quaternion(&self) -> crate::math::quaternion::Quaternion {
+    pub fn to_quaternion(&self) -> Quaternion {
         let [m00, m01, m02, m10, m11, m12, m20, m21, m22] = self.elems;
         let trace = m00 + m11 + m22;
         if trace >= 0.0 {
             let s = (trace + 1.0).sqrt();
             let v = 0.5 / s;
-            crate::math::quaternion::Quaternion::coords((m21 - m12) * v, (m02 - m20) * v, (m10 - m01) * v, 0.5 * s)
+            Quaternion::coords((m21 - m12) * v, (m02 - m20) * v, (m10 - m01) * v, 0.5 * s)
         } else {
             let max = m00.max(m11).max(m22);
             if m00 == max {
                 let s = (m00 - (m11 + m22) + 1.0).sqrt();
                 let v = 0.5 / s;
-                crate::math::quaternion::Quaternion::coords(0.5 * s, (m01 + m10) * v, (m20 + m02) * v, (m21 - m12) * v)
+                Quaternion::coords(0.5 * s, (m01 + m10) * v, (m20 + m02) * v, (m21 - m12) * v)
             } else if m11 == max {
                 let s = (m11 - (m22 + m00) + 1.0).sqrt();
                 let v = 0.5 / s;
-                crate::math::quaternion::Quaternion::coords((m01 + m10) * v, 0.5 * s, (m12 + m21) * v, (m02 - m20) * v)
+                Quaternion::coords((m01 + m10) * v, 0.5 * s, (m12 + m21) * v, (m02 - m20) * v)
             } else {
                 let s = (m22 - (m00 + m11) + 1.0).sqrt();
                 let v = 0.5 / s;
-                crate::math::quaternion::Quaternion::coords((m20 + m02) * v, (m12 + m21) * v, 0.5 * s, (m10 - m01) * v)
+                Quaternion::coords((m20 + m02) * v, (m12 + m21) * v, 0.5 * s, (m10 - m01) * v)
             }
         }
     }
+    // Eigenvalues and eigenvectors of a symmetric matrix by cyclic Jacobi rotations,
+    // with `self` = V diag(values) V^T. V is a rotation, its columns the eigenvectors.
+    pub fn symmetric_eigen(&self) -> (Vector, Matrix) {
+        let mut a = self.elems;
+        let mut v = Matrix::identity().elems;
+        for _ in 0..32 {
+            let off = sq(a[1]) + sq(a[2]) + sq(a[5]);
+            if off <= sq(EPSILON) * (sq(a[0]) + sq(a[4]) + sq(a[8])) {
+                break;
+            }
+            for (p, q) in [(0, 1), (0, 2), (1, 2)] {
+                let apq = a[3 * p + q];
+                if apq == 0.0 {
+                    continue;
+                }
+                // rotation in the p-q plane which zeroes a_pq
+                let theta = (a[3 * q + q] - a[3 * p + p]) / (2.0 * apq);
+                let t = theta.signum() / (theta.abs() + (sq(theta) + 1.0).sqrt());
+                let c = 1.0 / (sq(t) + 1.0).sqrt();
+                let s = t * c;
+                for k in 0..3 {
+                    let (akp, akq) = (a[3 * k + p], a[3 * k + q]);
+                    a[3 * k + p] = c * akp - s * akq;
+                    a[3 * k + q] = s * akp + c * akq;
+                    let (vkp, vkq) = (v[3 * k + p], v[3 * k + q]);
+                    v[3 * k + p] = c * vkp - s * vkq;
+                    v[3 * k + q] = s * vkp + c * vkq;
+                }
+                for k in 0..3 {
+                    let (apk, aqk) = (a[3 * p + k], a[3 * q + k]);
+                    a[3 * p + k] = c * apk - s * aqk;
+                    a[3 * q + k] = s * apk + c * aqk;
+                }
+            }
+        }
+        (Vector::new(a[0], a[4], a[8]), Matrix::new(v))
+    }
     pub fn inverse(&self) -> Option<Self> {
         let [m00, m01, m02, m10, m11, m12, m20, m21, m22] = self.elems;
         // cofactors
@@ -94,6 +158,44 @@ impl ApproxEq for Matrix {
     }
 }
 
+impl Add for &Matrix {
+    type Output = Matrix;
+    fn add(self, rhs: Self) -> Self::Output {
+        let mut elems = self.elems;
+        for (e, r) in elems.iter_mut().zip(rhs.elems) {
+            *e += r;
+        }
+        Matrix { elems }
+    }
+}
+
+impl Add<&Matrix> for Matrix {
+    type Output = Matrix;
+    fn add(self, rhs: &Matrix) -> Self::Output {
+        &self + rhs
+    }
+}
+
+impl Sub for &Matrix {
+    type Output = Matrix;
+    fn sub(self, rhs: Self) -> Self::Output {
+        let mut elems = self.elems;
+        for (e, r) in elems.iter_mut().zip(rhs.elems) {
+            *e -= r;
+        }
+        Matrix { elems }
+    }
+}
+
+impl Mul<f64> for &Matrix {
+    type Output = Matrix;
+    fn mul(self, rhs: f64) -> Self::Output {
+        Matrix {
+            elems: self.elems.map(|e| e * rhs),
+        }
+    }
+}
+
 impl Mul for &Matrix {
     type Output = Matrix;
 
@@ -101,9 +203,9 @@ impl Mul for &Matrix {
         let mut elems = [0f64; 9];
         for i in 0..3 {
             for j in 0..3 {
-                elems[3 * i + j] = self.elems[i * 3 + 0] * rhs.elems[0 * 3 + j]
-                    + self.elems[i * 3 + 1] * rhs.elems[1 * 3 + j]
-                    + self.elems[i * 3 + 2] * rhs.elems[2 * 3 + j];
+                elems[3 * i + j] = self.elems[i * 3] * rhs.elems[j]
+                    + self.elems[i * 3 + 1] * rhs.elems[3 + j]
+                    + self.elems[i * 3 + 2] * rhs.elems[6 + j];
             }
         }
         Matrix { elems }
@@ -218,6 +320,34 @@ mod tests {
         assert_approx_eq!(a.transpose(), tra);
     }
 
+    #[test]
+    fn test_matrix_columns() {
+        let a = Matrix::new([0.0, 9.0, 3.0, 9.0, 8.0, 0.0, 1.0, 8.0, 5.0]);
+        assert_approx_eq!(a.column(0), Vector::new(0.0, 9.0, 1.0));
+        assert_approx_eq!(a.column(2), Vector::new(3.0, 0.0, 5.0));
+    }
+
+    #[test]
+    fn test_outer_product_and_trace() {
+        let m = Matrix::outer(&Vector::new(1.0, 2.0, 3.0), &Vector::new(4.0, 5.0, 6.0));
+        assert_approx_eq!(
+            m,
+            Matrix::new([4.0, 5.0, 6.0, 8.0, 10.0, 12.0, 12.0, 15.0, 18.0])
+        );
+        assert_approx_eq!(m.trace(), 32.0);
+    }
+
+    #[test]
+    fn test_adding_and_scaling_matrices() {
+        let a = Matrix::new([1.0, 2.0, 3.0, 5.0, 6.0, 7.0, 9.0, 8.0, 7.0]);
+        assert_approx_eq!(&(&a + &a) - &a, a);
+        assert_approx_eq!(&a * 2.0, &a + &a);
+        assert_approx_eq!(
+            Matrix::diagonal(1.0, 1.0, 1.0) + &Matrix::diagonal(1.0, 2.0, 3.0),
+            Matrix::diagonal(2.0, 3.0, 4.0)
+        );
+    }
+
     #[test]
     fn test_transposing_the_identity_matrix() {
         assert_approx_eq!(IDENTITY.transpose(), IDENTITY);
@@ -253,34 +383,52 @@ mod tests {
     }
 
     #[test]
-    #[cfg(feature = "matrixtoquat")]
+    fn test_symmetric_eigen_of_diagonal_matrix() {
+        let (values, vectors) = Matrix::diagonal(3.0, 1.0, 2.0).symmetric_eigen();
+        assert_approx_eq!(values, Vector::new(3.0, 1.0, 2.0));
+        assert_approx_eq!(vectors, IDENTITY);
+    }
+
+    #[test]
+    fn test_symmetric_eigen() {
+        let a = Matrix::new([4.0, 1.0, -2.0, 1.0, 2.0, 0.5, -2.0, 0.5, 3.0]);
+        let (values, vectors) = a.symmetric_eigen();
+        let [x, y, z] = [values.x, values.y, values.z];
+        let rebuilt = &(&vectors * &Matrix::diagonal(x, y, z)) * &vectors.transpose();
+        assert_approx_eq!(rebuilt, a);
+        assert_approx_eq!(&vectors * &vectors.transpose(), IDENTITY);
+        // a proper rotation
+        assert_approx_eq!(vectors.to_quaternion().to_rotation_matrix(), vectors);
+    }
+
+    #[test]
     fn test_matrix_to_quaternion1() {
         // trace positive
-        let q = crate::math::quaternion::Quaternion::coords(1.0, 2.0, 3.0, 4.0).normalize();
+        let q = Quaternion::coords(1.0, 2.0, 3.0, 4.0).normalize();
         assert_approx_eq!(q.to_rotation_matrix().to_quaternion(), q);
     }
 
     #[test]
-    #[cfg(feature = "matrixtoquat")]
     fn test_matrix_to_quaternion2() {
         // m00 max
-        let q = crate::math::quaternion::Quaternion::from_rotation(&Vector::new(1.0, 0.0, 0.0), 3.0 * std::f64::consts::PI / 4.0);
+        let q = Quaternion::from_rotation(
+            &Vector::new(1.0, 0.0, 0.0),
+            3.0 * std::f64::consts::PI / 4.0,
+        );
         assert_approx_eq!(q.to_rotation_matrix().to_quaternion(), q);
     }
 
     #[test]
-    #[cfg(feature = "matrixtoquat")]
     fn test_matrix_to_quaternion3() {
         // m11 max
-        let q = crate::math::quaternion::Quaternion::coords(3.0, 4.0, 1.0, 2.0).normalize();
+        let q = Quaternion::coords(3.0, 4.0, 1.0, 2.0).normalize();
         assert_approx_eq!(q.to_rotation_matrix().to_quaternion(), q);
     }
 
     #[test]
-    #[cfg(feature = "matrixtoquat")]
     fn test_matrix_to_quaternion4() {
         // m22 max
-        let q = crate::math::quaternion::Quaternion::coords(2.0, 3.0, 4.0, 1.0).normalize();
+        let q = Quaternion::coords(2.0, 3.0, 4.0, 1.0).normalize();
         assert_approx_eq!(q.to_rotation_matrix().to_quaternion(), q);
     }
 }
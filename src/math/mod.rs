@@ -1,7 +1,7 @@
-mod approx_eq;
-pub(crate) mod matrix;
-pub(crate) mod quaternion;
-pub(crate) mod vector;
+pub mod approx_eq;
+pub mod matrix;
+pub mod quaternion;
+pub mod vector;
 
 #[inline(always)]
 pub fn sq(v: f64) -> f64 {
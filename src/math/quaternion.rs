@@ -23,7 +23,7 @@ impl Quaternion {
         Quaternion::new(axis * phi.sin(), phi.cos())
     }
     fn dot(&self, other: &Self) -> f64 {
-        self.v.dot(&self.v) + self.w * other.w
+        self.v.dot(&other.v) + self.w * other.w
     }
     fn magnitude(&self) -> f64 {
         self.dot(self).sqrt()
@@ -31,6 +31,22 @@ impl Quaternion {
     pub fn normalize(&self) -> Self {
         self / self.magnitude()
     }
+    // Spherical linear interpolation along the shorter arc, assumes unit quaternions
+    pub fn slerp(&self, other: &Self, t: f64) -> Self {
+        let mut cos = self.dot(other);
+        let mut other = other.clone();
+        if cos < 0.0 {
+            cos = -cos;
+            other = &other * -1.0;
+        }
+        if cos > 0.9995 {
+            // nearly parallel, fall back to normalized linear interpolation
+            return (&(self * (1.0 - t)) + &(&other * t)).normalize();
+        }
+        let angle = cos.acos();
+        let sin = angle.sin();
+        &(self * (((1.0 - t) * angle).sin() / sin)) + &(&other * ((t * angle).sin() / sin))
+    }
     pub fn conj(&self) -> Self {
         Self {
             v: -&self.v,
@@ -173,6 +189,27 @@ mod tests {
         assert_approx_eq!(&q2 * &q1, Quaternion::coords(32.0, 32.0, 56.0, -6.0));
     }
 
+    #[test]
+    fn test_slerp_halfway() {
+        let axis = Vector::new(0.0, 0.0, 1.0);
+        let a = Quaternion::from_rotation(&axis, 0.0);
+        let b = Quaternion::from_rotation(&axis, PI / 2.0);
+        assert_approx_eq!(a.slerp(&b, 0.5), Quaternion::from_rotation(&axis, PI / 4.0));
+        assert_approx_eq!(a.slerp(&b, 0.0), a);
+        assert_approx_eq!(a.slerp(&b, 1.0), b);
+    }
+
+    #[test]
+    fn test_slerp_takes_shorter_arc() {
+        let axis = Vector::new(1.0, 0.0, 0.0);
+        let a = Quaternion::from_rotation(&axis, 0.0);
+        // same rotation as PI / 2, but on the other hemisphere
+        let b = &Quaternion::from_rotation(&axis, PI / 2.0) * -1.0;
+        let halfway = a.slerp(&b, 0.5);
+        let expected = Quaternion::from_rotation(&axis, PI / 4.0);
+        assert_approx_eq!(halfway.to_rotation_matrix(), expected.to_rotation_matrix());
+    }
+
     #[test]
     fn test_unit_quaternions_to_rotations() {
         assert_approx_eq!(
@@ -1,5 +1,5 @@
 use crate::math::approx_eq::ApproxEq;
-use std::ops::{Add, Div, Mul, Neg};
+use std::ops::{Add, Div, Mul, Neg, Sub};
 
 #[derive(Debug, Clone)]
 pub struct Vector {
@@ -12,16 +12,39 @@ impl Vector {
     pub const fn new(x: f64, y: f64, z: f64) -> Self {
         Self { x, y, z }
     }
+    pub const fn zero() -> Self {
+        Self::new(0.0, 0.0, 0.0)
+    }
     pub fn dot(&self, other: &Self) -> f64 {
         self.x * other.x + self.y * other.y + self.z * other.z
     }
-    // pub fn cross(&self, b: &Self) -> Self {
-    //     Self {
-    //         x: self.y * b.z - self.z * b.y,
-    //         y: self.z * b.x - self.x * b.z,
-    //         z: self.x * b.y - self.y * b.x,
-    //     }
-    // }
+    pub fn cross(&self, b: &Self) -> Self {
+        Self {
+            x: self.y * b.z - self.z * b.y,
+            y: self.z * b.x - self.x * b.z,
+            z: self.x * b.y - self.y * b.x,
+        }
+    }
+    pub fn magnitude(&self) -> f64 {
+        self.dot(self).sqrt()
+    }
+    pub fn normalize(&self) -> Self {
+        self / self.magnitude()
+    }
+    pub fn lerp(&self, other: &Self, t: f64) -> Self {
+        self + &(&(other - self) * t)
+    }
+    // Two unit vectors completing `self`, assumed of unit length, to a right-handed basis
+    pub fn orthonormal_basis(&self) -> (Self, Self) {
+        let t = if self.x.abs() < 0.57735 {
+            Vector::new(0.0, self.z, -self.y)
+        } else {
+            Vector::new(self.y, -self.x, 0.0)
+        }
+        .normalize();
+        let u = self.cross(&t);
+        (t, u)
+    }
 }
 
 impl ApproxEq for Vector {
@@ -48,6 +71,24 @@ impl Add<&Vector> for Vector {
     }
 }
 
+impl Sub for &Vector {
+    type Output = Vector;
+    fn sub(self, rhs: Self) -> Self::Output {
+        Vector {
+            x: self.x - rhs.x,
+            y: self.y - rhs.y,
+            z: self.z - rhs.z,
+        }
+    }
+}
+
+impl Sub<&Vector> for Vector {
+    type Output = Vector;
+    fn sub(self, rhs: &Vector) -> Self::Output {
+        &self - rhs
+    }
+}
+
 impl Neg for &Vector {
     type Output = Vector;
     fn neg(self) -> Self::Output {
@@ -102,19 +143,19 @@ mod tests {
         assert_approx_eq!(p.z, 3.0);
     }
 
-    // #[test]
-    // fn test_subtracting_two_vectors() {
-    //     let v1 = Vector::new(3.0, 2.0, 1.0);
-    //     let v2 = Vector::new(5.0, 6.0, 7.0);
-    //     assert_approx_eq!(v1 - &v2, Vector::new(-2.0, -4.0, -6.0));
-    // }
+    #[test]
+    fn test_subtracting_two_vectors() {
+        let v1 = Vector::new(3.0, 2.0, 1.0);
+        let v2 = Vector::new(5.0, 6.0, 7.0);
+        assert_approx_eq!(v1 - &v2, Vector::new(-2.0, -4.0, -6.0));
+    }
 
-    // #[test]
-    // fn test_subtracting_a_vector_from_the_zero_vector() {
-    //     let zero = Vector::new(0.0, 0.0, 0.0);
-    //     let v = Vector::new(1.0, -2.0, 3.0);
-    //     assert_approx_eq!(zero - &v, Vector::new(-1.0, 2.0, -3.0));
-    // }
+    #[test]
+    fn test_subtracting_a_vector_from_the_zero_vector() {
+        let zero = Vector::new(0.0, 0.0, 0.0);
+        let v = Vector::new(1.0, -2.0, 3.0);
+        assert_approx_eq!(zero - &v, Vector::new(-1.0, 2.0, -3.0));
+    }
 
     #[test]
     fn test_negating_a_vector() {
@@ -140,57 +181,57 @@ mod tests {
         assert_approx_eq!(&a / 2.0, Vector::new(0.5, -1.0, 1.5));
     }
 
-    // #[test]
-    // fn test_the_magnitude_of_vector1() {
-    //     let v = Vector::new(1.0, 0.0, 0.0);
-    //     assert_approx_eq!(v.magnitude(), 1.0);
-    // }
-
-    // #[test]
-    // fn test_the_magnitude_of_vector2() {
-    //     let v = Vector::new(0.0, 1.0, 0.0);
-    //     assert_approx_eq!(v.magnitude(), 1.0);
-    // }
-
-    // #[test]
-    // fn test_the_magnitude_of_vector3() {
-    //     let v = Vector::new(0.0, 0.0, 1.0);
-    //     assert_approx_eq!(v.magnitude(), 1.0);
-    // }
-
-    // #[test]
-    // fn test_the_magnitude_of_vector4() {
-    //     let v = Vector::new(1.0, 2.0, 3.0);
-    //     assert_approx_eq!(v.magnitude(), (14f64).sqrt());
-    // }
-
-    // #[test]
-    // fn test_the_magnitude_of_vector5() {
-    //     let v = Vector::new(-1.0, -2.0, -3.0);
-    //     assert_approx_eq!(v.magnitude(), (14f64).sqrt());
-    // }
-
-    // #[test]
-    // fn test_normalizing_vector1() {
-    //     let v = Vector::new(4.0, 0.0, 0.0);
-    //     assert_approx_eq!(v.normalize(), Vector::new(1.0, 0.0, 0.0));
-    // }
-
-    // #[test]
-    // fn test_normalizing_vector2() {
-    //     let v = Vector::new(1.0, 2.0, 3.0);
-    //     assert_approx_eq!(
-    //         v.normalize(),
-    //         Vector::new(1.0 / 14f64.sqrt(), 2.0 / 14f64.sqrt(), 3.0 / 14f64.sqrt())
-    //     );
-    // }
-
-    // #[test]
-    // fn test_the_magnitude_of_a_normalized_vector() {
-    //     let v = Vector::new(1.0, 2.0, 3.0);
-    //     let norm = v.normalize();
-    //     assert_approx_eq!(norm.magnitude(), 1.0);
-    // }
+    #[test]
+    fn test_the_magnitude_of_vector1() {
+        let v = Vector::new(1.0, 0.0, 0.0);
+        assert_approx_eq!(v.magnitude(), 1.0);
+    }
+
+    #[test]
+    fn test_the_magnitude_of_vector2() {
+        let v = Vector::new(0.0, 1.0, 0.0);
+        assert_approx_eq!(v.magnitude(), 1.0);
+    }
+
+    #[test]
+    fn test_the_magnitude_of_vector3() {
+        let v = Vector::new(0.0, 0.0, 1.0);
+        assert_approx_eq!(v.magnitude(), 1.0);
+    }
+
+    #[test]
+    fn test_the_magnitude_of_vector4() {
+        let v = Vector::new(1.0, 2.0, 3.0);
+        assert_approx_eq!(v.magnitude(), (14f64).sqrt());
+    }
+
+    #[test]
+    fn test_the_magnitude_of_vector5() {
+        let v = Vector::new(-1.0, -2.0, -3.0);
+        assert_approx_eq!(v.magnitude(), (14f64).sqrt());
+    }
+
+    #[test]
+    fn test_normalizing_vector1() {
+        let v = Vector::new(4.0, 0.0, 0.0);
+        assert_approx_eq!(v.normalize(), Vector::new(1.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn test_normalizing_vector2() {
+        let v = Vector::new(1.0, 2.0, 3.0);
+        assert_approx_eq!(
+            v.normalize(),
+            Vector::new(1.0 / 14f64.sqrt(), 2.0 / 14f64.sqrt(), 3.0 / 14f64.sqrt())
+        );
+    }
+
+    #[test]
+    fn test_the_magnitude_of_a_normalized_vector() {
+        let v = Vector::new(1.0, 2.0, 3.0);
+        let norm = v.normalize();
+        assert_approx_eq!(norm.magnitude(), 1.0);
+    }
 
     #[test]
     fn test_the_dot_product_of_two_vectors() {
@@ -199,11 +240,32 @@ mod tests {
         assert_approx_eq!(a.dot(&b), 20.0);
     }
 
-    // #[test]
-    // fn test_the_cross_product_of_two_vectors() {
-    //     let a = Vector::new(1.0, 2.0, 3.0);
-    //     let b = Vector::new(2.0, 3.0, 4.0);
-    //     assert_approx_eq!(a.cross(&b), Vector::new(-1.0, 2.0, -1.0));
-    //     assert_approx_eq!(b.cross(&a), Vector::new(1.0, -2.0, 1.0));
-    // }
+    #[test]
+    fn test_lerp() {
+        let a = Vector::new(1.0, 2.0, 3.0);
+        let b = Vector::new(3.0, -2.0, 4.0);
+        assert_approx_eq!(a.lerp(&b, 0.25), Vector::new(1.5, 1.0, 3.25));
+    }
+
+    #[test]
+    fn test_orthonormal_basis() {
+        for n in [
+            Vector::new(1.0, 0.0, 0.0),
+            Vector::new(0.0, 0.0, -1.0),
+            Vector::new(1.0, 2.0, 3.0).normalize(),
+        ] {
+            let (t, u) = n.orthonormal_basis();
+            assert_approx_eq!(t.magnitude(), 1.0);
+            assert_approx_eq!(t.dot(&n), 0.0);
+            assert_approx_eq!(n.cross(&t), u);
+        }
+    }
+
+    #[test]
+    fn test_the_cross_product_of_two_vectors() {
+        let a = Vector::new(1.0, 2.0, 3.0);
+        let b = Vector::new(2.0, 3.0, 4.0);
+        assert_approx_eq!(a.cross(&b), Vector::new(-1.0, 2.0, -1.0));
+        assert_approx_eq!(b.cross(&a), Vector::new(1.0, -2.0, 1.0));
+    }
 }
@@ -0,0 +1,120 @@
+use kiss3d::resource::Mesh;
+use kiss3d::scene::SceneNode;
+use kiss3d::window::Window;
+use nalgebra::{Point3, Translation3, UnitQuaternion, Vector3};
+use physics_engine::math::quaternion::Quaternion;
+use physics_engine::math::vector::Vector;
+use physics_engine::world::handle::BodyHandle;
+use physics_engine::world::shape::Shape;
+use physics_engine::world::world::World;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::f32::consts::FRAC_PI_2;
+use std::rc::Rc;
+
+// Keeps one scene node per body in a `World`
+#[derive(Default)]
+pub struct Viewer {
+    nodes: HashMap<BodyHandle, SceneNode>,
+}
+
+impl Viewer {
+    // Adds, moves and removes nodes to match the current bodies of `world`
+    pub fn sync(&mut self, window: &mut Window, world: &World) {
+        let mut seen = HashSet::new();
+        world.for_each_interpolated(|handle: BodyHandle, p: &Vector, q: &Quaternion| {
+            let node = self.nodes.entry(handle).or_insert_with(|| {
+                let mut group = window.add_group();
+                add_shape(&mut group, world.get(handle).unwrap().shape());
+                group
+            });
+            node.set_local_translation(Translation3::new(p.x as f32, p.y as f32, p.z as f32));
+            node.set_local_rotation(to_unit_quaternion(q));
+            seen.insert(handle);
+        });
+        self.nodes.retain(|handle, node| {
+            if !seen.contains(handle) {
+                window.remove_node(node);
+            }
+            seen.contains(handle)
+        });
+    }
+}
+
+fn to_unit_quaternion(q: &Quaternion) -> UnitQuaternion<f32> {
+    UnitQuaternion::from_quaternion(nalgebra::Quaternion::new(
+        q.w as f32,
+        q.v.x as f32,
+        q.v.y as f32,
+        q.v.z as f32,
+    ))
+}
+
+// Adds a node for `shape` below the body node `parent`
+fn add_shape(parent: &mut SceneNode, shape: &Shape) {
+    // kiss3d builds round shapes along y, the bodies have them along z
+    let y_to_z = UnitQuaternion::from_axis_angle(&Vector3::x_axis(), FRAC_PI_2);
+    let mut node = match shape {
+        Shape::Box { half_extents } => parent.add_cube(
+            2.0 * half_extents.x as f32,
+            2.0 * half_extents.y as f32,
+            2.0 * half_extents.z as f32,
+        ),
+        Shape::Sphere { radius } => parent.add_sphere(*radius as f32),
+        Shape::Capsule {
+            radius,
+            half_height,
+        } => {
+            let mut node = parent.add_capsule(*radius as f32, 2.0 * *half_height as f32);
+            node.set_local_rotation(y_to_z);
+            node
+        }
+        Shape::Cylinder {
+            radius,
+            half_height,
+        } => {
+            let mut node = parent.add_cylinder(*radius as f32, 2.0 * *half_height as f32);
+            node.set_local_rotation(y_to_z);
+            node
+        }
+        Shape::Cone { radius, height } => {
+            // kiss3d centers the cone halfway between base and apex
+            let mut node = parent.add_cone(*radius as f32, *height as f32);
+            node.set_local_rotation(y_to_z);
+            node.set_local_translation(Translation3::new(0.0, 0.0, 0.25 * *height as f32));
+            node
+        }
+        Shape::ConvexHull {
+            vertices,
+            triangles,
+        }
+        | Shape::TriMesh {
+            vertices,
+            triangles,
+        } => {
+            let coords = vertices
+                .iter()
+                .map(|v| Point3::new(v.x as f32, v.y as f32, v.z as f32))
+                .collect();
+            let faces = triangles
+                .iter()
+                .map(|[i, j, k]| Point3::new(*i as u16, *j as u16, *k as u16))
+                .collect();
+            let mesh = Mesh::new(coords, faces, None, None, false);
+            parent.add_mesh(Rc::new(RefCell::new(mesh)), Vector3::new(1.0, 1.0, 1.0))
+        }
+        Shape::Compound { children } => {
+            let mut group = parent.add_group();
+            for child in children {
+                let p = &child.position;
+                let mut child_node = group.add_group();
+                child_node
+                    .set_local_translation(Translation3::new(p.x as f32, p.y as f32, p.z as f32));
+                child_node.set_local_rotation(to_unit_quaternion(&child.orientation));
+                add_shape(&mut child_node, &child.shape);
+            }
+            group
+        }
+    };
+    node.set_color(1.0, 1.0, 1.0);
+}
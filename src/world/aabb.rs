@@ -0,0 +1,186 @@
+use super::collision::posed_parts;
+use super::collision::ray::ray_box;
+use super::shape::Shape;
+use crate::math::matrix::Matrix;
+use crate::math::sq;
+use crate::math::vector::Vector;
+
+// Axis aligned bounding box in world coordinates
+#[derive(Debug, Clone)]
+pub struct Aabb {
+    pub min: Vector,
+    pub max: Vector,
+}
+
+impl Aabb {
+    pub const fn new(min: Vector, max: Vector) -> Self {
+        Self { min, max }
+    }
+    pub fn around(center: &Vector, extents: &Vector) -> Self {
+        Self::new(center - extents, center + extents)
+    }
+    pub fn of_points(points: &[Vector]) -> Self {
+        let mut aabb = Self::new(points[0].clone(), points[0].clone());
+        for p in &points[1..] {
+            aabb = aabb.merge(&Self::new(p.clone(), p.clone()));
+        }
+        aabb
+    }
+    // Bounds of `shape` placed at `x` with orientation `r`
+    pub fn of_shape(x: &Vector, r: &Matrix, shape: &Shape) -> Self {
+        let axis = r.column(2);
+        // extents of a disc of the given radius perpendicular to the z axis of `r`
+        let disc = |radius: f64| {
+            Vector::new(
+                radius * (1.0 - sq(axis.x)).max(0.0).sqrt(),
+                radius * (1.0 - sq(axis.y)).max(0.0).sqrt(),
+                radius * (1.0 - sq(axis.z)).max(0.0).sqrt(),
+            )
+        };
+        match shape {
+            Shape::Box { half_extents } => {
+                let rows = r.transpose();
+                let h = half_extents;
+                let extent = |i: usize| {
+                    let row = rows.column(i);
+                    row.x.abs() * h.x + row.y.abs() * h.y + row.z.abs() * h.z
+                };
+                Self::around(x, &Vector::new(extent(0), extent(1), extent(2)))
+            }
+            Shape::Sphere { radius } => Self::around(x, &Vector::new(*radius, *radius, *radius)),
+            Shape::Capsule {
+                radius,
+                half_height,
+            } => {
+                let a = &axis * *half_height;
+                Self::around(
+                    x,
+                    &Vector::new(a.x.abs() + radius, a.y.abs() + radius, a.z.abs() + radius),
+                )
+            }
+            Shape::Cylinder {
+                radius,
+                half_height,
+            } => {
+                let a = &axis * *half_height;
+                let d = disc(*radius);
+                Self::around(
+                    x,
+                    &Vector::new(a.x.abs() + d.x, a.y.abs() + d.y, a.z.abs() + d.z),
+                )
+            }
+            Shape::Cone { radius, height } => {
+                let base = x - &(&axis * (0.25 * height));
+                let apex = x + &(&axis * (0.75 * height));
+                Self::around(&base, &disc(*radius)).merge(&Self::new(apex.clone(), apex))
+            }
+            Shape::ConvexHull { vertices, .. } | Shape::TriMesh { vertices, .. } => {
+                let points: Vec<Vector> = vertices.iter().map(|v| x + &(r * v)).collect();
+                Self::of_points(&points)
+            }
+            Shape::Compound { .. } => posed_parts(x, r, shape)
+                .iter()
+                .map(|(x, r, part)| Self::of_shape(x, r, part))
+                .reduce(|a, b| a.merge(&b))
+                .unwrap_or_else(|| Self::new(x.clone(), x.clone())),
+        }
+    }
+    pub fn merge(&self, other: &Self) -> Self {
+        Self::new(
+            Vector::new(
+                self.min.x.min(other.min.x),
+                self.min.y.min(other.min.y),
+                self.min.z.min(other.min.z),
+            ),
+            Vector::new(
+                self.max.x.max(other.max.x),
+                self.max.y.max(other.max.y),
+                self.max.z.max(other.max.z),
+            ),
+        )
+    }
+    pub fn expand(&self, margin: f64) -> Self {
+        let m = Vector::new(margin, margin, margin);
+        Self::new(&self.min - &m, &self.max + &m)
+    }
+    pub fn overlaps(&self, other: &Self) -> bool {
+        self.min.x <= other.max.x
+            && other.min.x <= self.max.x
+            && self.min.y <= other.max.y
+            && other.min.y <= self.max.y
+            && self.min.z <= other.max.z
+            && other.min.z <= self.max.z
+    }
+    pub fn contains(&self, other: &Self) -> bool {
+        self.min.x <= other.min.x
+            && self.min.y <= other.min.y
+            && self.min.z <= other.min.z
+            && other.max.x <= self.max.x
+            && other.max.y <= self.max.y
+            && other.max.z <= self.max.z
+    }
+    // Whether the ray `origin + t direction` meets the box for some 0 <= t <= max_t
+    pub fn hit_by_ray(&self, origin: &Vector, direction: &Vector, max_t: f64) -> bool {
+        let center = &(&self.min + &self.max) * 0.5;
+        let half_extents = &(&self.max - &self.min) * 0.5;
+        ray_box(&(origin - &center), direction, &half_extents).is_some_and(|hit| hit.t <= max_t)
+    }
+    pub fn surface_area(&self) -> f64 {
+        let d = &self.max - &self.min;
+        2.0 * (d.x * d.y + d.y * d.z + d.z * d.x)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::approx_eq::{assert_approx_eq, ApproxEq};
+    use crate::math::quaternion::Quaternion;
+    use std::f64::consts::FRAC_PI_4;
+
+    #[test]
+    fn test_rotated_box() {
+        let r =
+            Quaternion::from_rotation(&Vector::new(0.0, 0.0, 1.0), FRAC_PI_4).to_rotation_matrix();
+        let shape = Shape::Box {
+            half_extents: Vector::new(1.0, 1.0, 0.5),
+        };
+        let aabb = Aabb::of_shape(&Vector::new(1.0, 0.0, 0.0), &r, &shape);
+        let s = 2.0_f64.sqrt();
+        assert_approx_eq!(aabb.min, Vector::new(1.0 - s, -s, -0.5));
+        assert_approx_eq!(aabb.max, Vector::new(1.0 + s, s, 0.5));
+    }
+
+    #[test]
+    fn test_tilted_cylinder() {
+        // lying along x
+        let r = Quaternion::from_rotation(&Vector::new(0.0, 1.0, 0.0), 2.0 * FRAC_PI_4)
+            .to_rotation_matrix();
+        let shape = Shape::Cylinder {
+            radius: 0.5,
+            half_height: 2.0,
+        };
+        let aabb = Aabb::of_shape(&Vector::zero(), &r, &shape);
+        assert_approx_eq!(aabb.max, Vector::new(2.0, 0.5, 0.5));
+    }
+
+    #[test]
+    fn test_overlap_and_containment() {
+        let a = Aabb::new(Vector::zero(), Vector::new(1.0, 1.0, 1.0));
+        let b = Aabb::new(Vector::new(0.5, 0.5, 0.5), Vector::new(2.0, 2.0, 2.0));
+        let c = Aabb::new(Vector::new(1.5, 0.0, 0.0), Vector::new(2.0, 1.0, 1.0));
+        assert!(a.overlaps(&b) && b.overlaps(&c) && !a.overlaps(&c));
+        assert!(a.merge(&c).contains(&a) && !a.contains(&b));
+        assert_approx_eq!(a.surface_area(), 6.0);
+    }
+
+    #[test]
+    fn test_hit_by_ray() {
+        let aabb = Aabb::new(Vector::zero(), Vector::new(1.0, 1.0, 1.0));
+        let origin = Vector::new(-1.0, 0.5, 0.5);
+        let direction = Vector::new(1.0, 0.0, 0.0);
+        assert!(aabb.hit_by_ray(&origin, &direction, 1.5));
+        assert!(!aabb.hit_by_ray(&origin, &direction, 0.5));
+        assert!(!aabb.hit_by_ray(&origin, &-&direction, 10.0));
+    }
+}
@@ -0,0 +1,10 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BodyType {
+    // never moves
+    Static,
+    // moves with the velocity it is given, pushing dynamic bodies without being pushed
+    Kinematic,
+    // moved by gravity, forces, contacts and joints
+    #[default]
+    Dynamic,
+}
@@ -0,0 +1,312 @@
+use super::BroadPhase;
+use crate::math::vector::Vector;
+use crate::world::aabb::Aabb;
+
+struct Node {
+    aabb: Aabb,
+    parent: Option<usize>,
+    // children of internal nodes, None for leaves
+    children: Option<[usize; 2]>,
+    // the body of a leaf
+    body: usize,
+    // longest path down to a leaf, zero for leaves
+    height: usize,
+}
+
+// Bounding volume hierarchy over fattened body boxes. A leaf is only reinserted
+// when its body leaves the fattened box, so slowly moving bodies rarely touch the
+// tree. Rotations keep the heights of siblings within one of each other, so bodies
+// added in order, along a line or in a stack, do not degrade it into a list.
+pub struct DynamicAabbTree {
+    nodes: Vec<Node>,
+    free: Vec<usize>,
+    root: Option<usize>,
+    // leaf node of each body
+    leaves: Vec<usize>,
+    margin: f64,
+}
+
+impl Default for DynamicAabbTree {
+    fn default() -> Self {
+        Self::new(0.1)
+    }
+}
+
+impl DynamicAabbTree {
+    // `margin` is how far leaf boxes extend beyond the bodies
+    pub fn new(margin: f64) -> Self {
+        Self {
+            nodes: Vec::new(),
+            free: Vec::new(),
+            root: None,
+            leaves: Vec::new(),
+            margin,
+        }
+    }
+    fn allocate(&mut self, node: Node) -> usize {
+        match self.free.pop() {
+            Some(index) => {
+                self.nodes[index] = node;
+                index
+            }
+            None => {
+                self.nodes.push(node);
+                self.nodes.len() - 1
+            }
+        }
+    }
+    fn insert(&mut self, body: usize, aabb: Aabb) -> usize {
+        let leaf = self.allocate(Node {
+            aabb,
+            parent: None,
+            children: None,
+            body,
+            height: 0,
+        });
+        let Some(root) = self.root else {
+            self.root = Some(leaf);
+            return leaf;
+        };
+        // descend towards the child whose box grows least
+        let aabb = self.nodes[leaf].aabb.clone();
+        let mut sibling = root;
+        while let Some([left, right]) = self.nodes[sibling].children {
+            let growth = |node: usize| {
+                let current = &self.nodes[node].aabb;
+                current.merge(&aabb).surface_area() - current.surface_area()
+            };
+            sibling = if growth(left) <= growth(right) {
+                left
+            } else {
+                right
+            };
+        }
+        let old_parent = self.nodes[sibling].parent;
+        let parent = self.allocate(Node {
+            aabb: self.nodes[sibling].aabb.merge(&aabb),
+            parent: old_parent,
+            children: Some([sibling, leaf]),
+            body: 0,
+            height: 1,
+        });
+        self.nodes[sibling].parent = Some(parent);
+        self.nodes[leaf].parent = Some(parent);
+        match old_parent {
+            Some(old_parent) => self.replace_child(old_parent, sibling, parent),
+            None => self.root = Some(parent),
+        }
+        self.refit(parent);
+        leaf
+    }
+    fn remove(&mut self, leaf: usize) {
+        self.free.push(leaf);
+        let Some(parent) = self.nodes[leaf].parent else {
+            self.root = None;
+            return;
+        };
+        let [left, right] = self.nodes[parent].children.unwrap();
+        let sibling = if left == leaf { right } else { left };
+        let grandparent = self.nodes[parent].parent;
+        self.nodes[sibling].parent = grandparent;
+        self.free.push(parent);
+        match grandparent {
+            Some(grandparent) => {
+                self.replace_child(grandparent, parent, sibling);
+                self.refit(grandparent);
+            }
+            None => self.root = Some(sibling),
+        }
+    }
+    fn replace_child(&mut self, node: usize, old: usize, new: usize) {
+        if let Some(children) = &mut self.nodes[node].children {
+            for child in children {
+                if *child == old {
+                    *child = new;
+                }
+            }
+        }
+    }
+    // Rebalances and recomputes the boxes and heights from `node` up to the root
+    fn refit(&mut self, node: usize) {
+        let mut current = Some(node);
+        while let Some(node) = current {
+            let node = self.balance(node);
+            self.fit(node);
+            current = self.nodes[node].parent;
+        }
+    }
+    // Recomputes the box and height of an internal node from its children
+    fn fit(&mut self, node: usize) {
+        let [left, right] = self.nodes[node].children.unwrap();
+        let (left, right) = (&self.nodes[left], &self.nodes[right]);
+        let aabb = left.aabb.merge(&right.aabb);
+        let height = 1 + left.height.max(right.height);
+        self.nodes[node].aabb = aabb;
+        self.nodes[node].height = height;
+    }
+    // Lifts the taller child of `a` in its place if it is more than one level
+    // taller than the other, returning the node now at that place
+    fn balance(&mut self, a: usize) -> usize {
+        let Some(children) = self.nodes[a].children else {
+            return a;
+        };
+        let [left, right] = children;
+        let difference = self.nodes[right].height as isize - self.nodes[left].height as isize;
+        // side of the child to lift
+        let side = match difference {
+            2.. => 1,
+            ..=-2 => 0,
+            _ => return a,
+        };
+        let c = children[side];
+        let [f, g] = self.nodes[c].children.unwrap();
+        // c keeps its taller child and hands the other to a, in c's old place
+        let (keep, give) = if self.nodes[f].height > self.nodes[g].height {
+            (f, g)
+        } else {
+            (g, f)
+        };
+        let parent = self.nodes[a].parent;
+        self.nodes[c].parent = parent;
+        match parent {
+            Some(parent) => self.replace_child(parent, a, c),
+            None => self.root = Some(c),
+        }
+        self.nodes[c].children = Some([a, keep]);
+        self.nodes[a].parent = Some(c);
+        self.replace_child(a, c, give);
+        self.nodes[give].parent = Some(a);
+        self.fit(a);
+        self.fit(c);
+        c
+    }
+    // Bodies whose fattened boxes overlap `aabb`
+    pub fn query(&self, aabb: &Aabb) -> Vec<usize> {
+        let mut found = Vec::new();
+        let mut stack: Vec<usize> = self.root.into_iter().collect();
+        while let Some(node) = stack.pop() {
+            let node = &self.nodes[node];
+            if !node.aabb.overlaps(aabb) {
+                continue;
+            }
+            match node.children {
+                Some(children) => stack.extend(children),
+                None => found.push(node.body),
+            }
+        }
+        found
+    }
+}
+
+impl BroadPhase for DynamicAabbTree {
+    // Reinserts the bodies which left their leaves
+    fn sync(&mut self, aabbs: &[Aabb]) {
+        while self.leaves.len() > aabbs.len() {
+            let leaf = self.leaves.pop().unwrap();
+            self.remove(leaf);
+        }
+        for (body, aabb) in aabbs.iter().enumerate() {
+            match self.leaves.get(body) {
+                Some(&leaf) if self.nodes[leaf].aabb.contains(aabb) => (),
+                Some(&leaf) => {
+                    self.remove(leaf);
+                    self.leaves[body] = self.insert(body, aabb.expand(self.margin));
+                }
+                None => {
+                    let leaf = self.insert(body, aabb.expand(self.margin));
+                    self.leaves.push(leaf);
+                }
+            }
+        }
+    }
+    fn update(&mut self, aabbs: &[Aabb]) -> Vec<(usize, usize)> {
+        self.sync(aabbs);
+        let mut pairs = Vec::new();
+        for (a, aabb) in aabbs.iter().enumerate() {
+            for b in self.query(aabb) {
+                if a < b && aabb.overlaps(&aabbs[b]) {
+                    pairs.push((a, b));
+                }
+            }
+        }
+        pairs
+    }
+    fn cast_ray(
+        &self,
+        aabbs: &[Aabb],
+        origin: &Vector,
+        direction: &Vector,
+        max_t: f64,
+    ) -> Vec<usize> {
+        let mut found = Vec::new();
+        let mut stack: Vec<usize> = self.root.into_iter().collect();
+        while let Some(node) = stack.pop() {
+            let node = &self.nodes[node];
+            if !node.aabb.hit_by_ray(origin, direction, max_t) {
+                continue;
+            }
+            match node.children {
+                Some(children) => stack.extend(children),
+                None if aabbs[node.body].hit_by_ray(origin, direction, max_t) => {
+                    found.push(node.body)
+                }
+                None => (),
+            }
+        }
+        found
+    }
+    fn overlapping(&self, aabbs: &[Aabb], aabb: &Aabb) -> Vec<usize> {
+        let mut found = self.query(aabb);
+        found.retain(|&i| aabbs[i].overlaps(aabb));
+        found
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::broad_phase::tests::{
+        check_against_brute_force, check_overlaps_against_brute_force,
+        check_rays_against_brute_force,
+    };
+
+    #[test]
+    fn test_matches_brute_force() {
+        check_against_brute_force(&mut DynamicAabbTree::default());
+    }
+
+    #[test]
+    fn test_rays_match_brute_force() {
+        check_rays_against_brute_force(&mut DynamicAabbTree::default());
+    }
+
+    #[test]
+    fn test_overlaps_match_brute_force() {
+        check_overlaps_against_brute_force(&mut DynamicAabbTree::default());
+    }
+
+    #[test]
+    fn test_stays_balanced_when_filled_in_order() {
+        let mut tree = DynamicAabbTree::new(0.0);
+        let aabbs: Vec<Aabb> = (0..1024)
+            .map(|i| {
+                let min = Vector::new(0.0, 0.0, 2.0 * i as f64);
+                Aabb::new(min.clone(), &min + &Vector::new(1.0, 1.0, 1.0))
+            })
+            .collect();
+        tree.update(&aabbs);
+        let root = &tree.nodes[tree.root.unwrap()];
+        // a list would be 1023 high, a perfect tree 10
+        assert!(root.height <= 15, "height = {}", root.height);
+        assert_eq!(tree.query(&aabbs[700]), [700]);
+    }
+
+    #[test]
+    fn test_query_finds_overlapping_leaves() {
+        let mut tree = DynamicAabbTree::new(0.0);
+        let aabbs = crate::world::broad_phase::tests::scattered(20, 3, 10.0);
+        tree.update(&aabbs);
+        let probe = Aabb::new(aabbs[5].min.clone(), aabbs[5].min.clone());
+        assert!(tree.query(&probe).contains(&5));
+    }
+}
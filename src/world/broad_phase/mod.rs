@@ -0,0 +1,118 @@
+use super::aabb::Aabb;
+use crate::math::vector::Vector;
+
+pub mod dynamic_aabb_tree;
+pub mod sweep_and_prune;
+
+// Finds the pairs of bodies whose bounding boxes overlap. Bodies are numbered by
+// their position in `aabbs`, which may grow, shrink or reorder between calls;
+// implementations may keep state to exploit coherence from call to call.
+// The queries take the boxes of the last `sync` or `update`.
+pub trait BroadPhase {
+    // Brings the state up to date with `aabbs`
+    fn sync(&mut self, aabbs: &[Aabb]);
+    // Syncs, then returns the pairs (a, b) with a < b, in no particular order
+    fn update(&mut self, aabbs: &[Aabb]) -> Vec<(usize, usize)>;
+    // Bodies whose boxes the ray `origin + t direction` meets for 0 <= t <= max_t,
+    // in no particular order
+    fn cast_ray(
+        &self,
+        aabbs: &[Aabb],
+        origin: &Vector,
+        direction: &Vector,
+        max_t: f64,
+    ) -> Vec<usize>;
+    // Bodies whose boxes overlap `aabb`, in no particular order
+    fn overlapping(&self, aabbs: &[Aabb], aabb: &Aabb) -> Vec<usize>;
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    pub(crate) fn brute_force(aabbs: &[Aabb]) -> Vec<(usize, usize)> {
+        let mut pairs = Vec::new();
+        for b in 0..aabbs.len() {
+            for a in 0..b {
+                if aabbs[a].overlaps(&aabbs[b]) {
+                    pairs.push((a, b));
+                }
+            }
+        }
+        pairs
+    }
+
+    // Unit boxes scattered by a simple linear congruential generator
+    pub(crate) fn scattered(count: usize, seed: u64, spread: f64) -> Vec<Aabb> {
+        let mut state = seed;
+        let mut next = || {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (state >> 11) as f64 / (1u64 << 53) as f64 * spread
+        };
+        (0..count)
+            .map(|_| {
+                let min = Vector::new(next(), next(), next());
+                let max = &min + &Vector::new(1.0, 1.0, 1.0);
+                Aabb::new(min, max)
+            })
+            .collect()
+    }
+
+    // Runs the broad phase over several frames of moving, added and removed
+    // boxes and compares against testing every pair
+    pub(crate) fn check_against_brute_force(broad_phase: &mut dyn BroadPhase) {
+        let mut aabbs = scattered(60, 7, 10.0);
+        for frame in 0..20 {
+            let moved = scattered(aabbs.len(), frame, 0.5);
+            for (aabb, offset) in aabbs.iter_mut().zip(&moved) {
+                *aabb = Aabb::new(&aabb.min + &offset.min, &aabb.max + &offset.min);
+            }
+            match frame % 3 {
+                1 => aabbs.extend(scattered(3, frame + 100, 10.0)),
+                2 => {
+                    aabbs.swap_remove(frame as usize);
+                }
+                _ => (),
+            }
+            let mut pairs = broad_phase.update(&aabbs);
+            pairs.sort_unstable();
+            let mut expected = brute_force(&aabbs);
+            expected.sort_unstable();
+            assert_eq!(pairs, expected, "frame {}", frame);
+        }
+    }
+
+    // Casts rays at scattered boxes and compares against testing every box
+    pub(crate) fn check_rays_against_brute_force(broad_phase: &mut dyn BroadPhase) {
+        let aabbs = scattered(60, 11, 10.0);
+        broad_phase.sync(&aabbs);
+        let origins = scattered(10, 5, 12.0);
+        for (i, origin) in origins.iter().enumerate() {
+            let target = &(&aabbs[6 * i].min + &aabbs[6 * i].max) * 0.5;
+            let direction = (target - &origin.min).normalize();
+            let mut found = broad_phase.cast_ray(&aabbs, &origin.min, &direction, 20.0);
+            found.sort_unstable();
+            let expected: Vec<usize> = (0..aabbs.len())
+                .filter(|&i| aabbs[i].hit_by_ray(&origin.min, &direction, 20.0))
+                .collect();
+            assert!(expected.contains(&(6 * i)));
+            assert_eq!(found, expected);
+        }
+    }
+
+    pub(crate) fn check_overlaps_against_brute_force(broad_phase: &mut dyn BroadPhase) {
+        let aabbs = scattered(60, 13, 10.0);
+        broad_phase.sync(&aabbs);
+        for probe in scattered(10, 17, 10.0) {
+            let probe = probe.expand(1.0);
+            let mut found = broad_phase.overlapping(&aabbs, &probe);
+            found.sort_unstable();
+            let expected: Vec<usize> = (0..aabbs.len())
+                .filter(|&i| aabbs[i].overlaps(&probe))
+                .collect();
+            assert_eq!(found, expected);
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use super::BroadPhase;
+use crate::math::vector::Vector;
+use crate::world::aabb::Aabb;
+
+// Keeps the boxes sorted by their lower bound along x between calls. Bodies move
+// little from step to step, so the insertion sort that restores the order is
+// close to linear.
+#[derive(Default)]
+pub struct SweepAndPrune {
+    // body indices by ascending lower x bound
+    order: Vec<usize>,
+}
+
+impl SweepAndPrune {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl BroadPhase for SweepAndPrune {
+    fn sync(&mut self, aabbs: &[Aabb]) {
+        self.order.retain(|&i| i < aabbs.len());
+        let known = self.order.len();
+        self.order.extend(known..aabbs.len());
+        // insertion sort
+        for i in 1..self.order.len() {
+            let mut j = i;
+            while j > 0 && aabbs[self.order[j - 1]].min.x > aabbs[self.order[j]].min.x {
+                self.order.swap(j - 1, j);
+                j -= 1;
+            }
+        }
+    }
+    fn update(&mut self, aabbs: &[Aabb]) -> Vec<(usize, usize)> {
+        self.sync(aabbs);
+        let mut pairs = Vec::new();
+        for (i, &a) in self.order.iter().enumerate() {
+            for &b in &self.order[i + 1..] {
+                if aabbs[b].min.x > aabbs[a].max.x {
+                    break;
+                }
+                if aabbs[a].overlaps(&aabbs[b]) {
+                    pairs.push((a.min(b), a.max(b)));
+                }
+            }
+        }
+        pairs
+    }
+    fn cast_ray(
+        &self,
+        aabbs: &[Aabb],
+        origin: &Vector,
+        direction: &Vector,
+        max_t: f64,
+    ) -> Vec<usize> {
+        // only boxes starting before the far end of the ray along x can be hit
+        let reach = origin.x + (direction.x * max_t).max(0.0);
+        self.order
+            .iter()
+            .take_while(|&&i| aabbs[i].min.x <= reach)
+            .copied()
+            .filter(|&i| aabbs[i].hit_by_ray(origin, direction, max_t))
+            .collect()
+    }
+    fn overlapping(&self, aabbs: &[Aabb], aabb: &Aabb) -> Vec<usize> {
+        self.order
+            .iter()
+            .take_while(|&&i| aabbs[i].min.x <= aabb.max.x)
+            .copied()
+            .filter(|&i| aabbs[i].overlaps(aabb))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::broad_phase::tests::{
+        check_against_brute_force, check_overlaps_against_brute_force,
+        check_rays_against_brute_force,
+    };
+
+    #[test]
+    fn test_matches_brute_force() {
+        check_against_brute_force(&mut SweepAndPrune::new());
+    }
+
+    #[test]
+    fn test_rays_match_brute_force() {
+        check_rays_against_brute_force(&mut SweepAndPrune::new());
+    }
+
+    #[test]
+    fn test_overlaps_match_brute_force() {
+        check_overlaps_against_brute_force(&mut SweepAndPrune::new());
+    }
+}
@@ -0,0 +1,325 @@
+use super::{ContactManifold, ContactPoint};
+use crate::math::matrix::Matrix;
+use crate::math::vector::Vector;
+use crate::world::rigid_body_state::RigidBodyState;
+
+// Cross product axes shorter than this are treated as parallel edges
+const PARALLEL_EPSILON: f64 = 1e-6;
+// Edge axes must beat the best face axis by this factor to be chosen
+const EDGE_BIAS: f64 = 0.95;
+
+enum Axis {
+    FaceA(usize),
+    FaceB(usize),
+    Edge(usize, usize),
+}
+
+// Contacts between box A and box B by the separating axis theorem
+pub fn box_box(
+    a: &RigidBodyState,
+    half_a: &Vector,
+    b: &RigidBodyState,
+    half_b: &Vector,
+) -> Option<ContactManifold> {
+    box_box_posed(&a.x, &a.r, half_a, &b.x, &b.r, half_b)
+}
+
+pub(crate) fn box_box_posed(
+    xa: &Vector,
+    ra: &Matrix,
+    half_a: &Vector,
+    xb: &Vector,
+    rb: &Matrix,
+    half_b: &Vector,
+) -> Option<ContactManifold> {
+    let axes_a = [ra.column(0), ra.column(1), ra.column(2)];
+    let axes_b = [rb.column(0), rb.column(1), rb.column(2)];
+    let ha = [half_a.x, half_a.y, half_a.z];
+    let hb = [half_b.x, half_b.y, half_b.z];
+    let t = xb - xa;
+
+    // overlap of the projections on the unit axis `l`, negative when separated
+    let overlap = |l: &Vector| {
+        let pa: f64 = (0..3).map(|i| ha[i] * axes_a[i].dot(l).abs()).sum();
+        let pb: f64 = (0..3).map(|i| hb[i] * axes_b[i].dot(l).abs()).sum();
+        pa + pb - t.dot(l).abs()
+    };
+
+    let mut best: Option<(f64, Vector, Axis)> = None;
+    for i in 0..3 {
+        for (l, axis) in [
+            (axes_a[i].clone(), Axis::FaceA(i)),
+            (axes_b[i].clone(), Axis::FaceB(i)),
+        ] {
+            let depth = overlap(&l);
+            if depth < 0.0 {
+                return None;
+            }
+            if best.as_ref().is_none_or(|(d, _, _)| depth < *d) {
+                best = Some((depth, l, axis));
+            }
+        }
+    }
+    for (i, axis_a) in axes_a.iter().enumerate() {
+        for (j, axis_b) in axes_b.iter().enumerate() {
+            let l = axis_a.cross(axis_b);
+            let length = l.magnitude();
+            if length < PARALLEL_EPSILON {
+                continue;
+            }
+            let l = &l / length;
+            let depth = overlap(&l);
+            if depth < 0.0 {
+                return None;
+            }
+            if best.as_ref().is_none_or(|(d, _, _)| depth < EDGE_BIAS * *d) {
+                best = Some((depth, l, Axis::Edge(i, j)));
+            }
+        }
+    }
+
+    let (depth, l, axis) = best?;
+    let normal = if t.dot(&l) < 0.0 { -l } else { l };
+    let points = match axis {
+        Axis::FaceA(i) => face_contacts(xa, &axes_a, &ha, i, &normal, xb, &axes_b, &hb),
+        Axis::FaceB(j) => face_contacts(xb, &axes_b, &hb, j, &-&normal, xa, &axes_a, &ha),
+        Axis::Edge(i, j) => {
+            let pa = edge_point(xa, &axes_a, &ha, i, &normal);
+            let pb = edge_point(xb, &axes_b, &hb, j, &-&normal);
+            let (ca, cb) = closest_points_on_lines(&pa, &axes_a[i], &pb, &axes_b[j]);
+            vec![ContactPoint {
+                point: &(&ca + &cb) * 0.5,
+                depth,
+            }]
+        }
+    };
+    if points.is_empty() {
+        None
+    } else {
+        Some(ContactManifold { normal, points })
+    }
+}
+
+// Clips the incident face of box `inc` against the reference face `face` of box `rf`,
+// whose outward normal is `n`
+#[allow(clippy::too_many_arguments)]
+fn face_contacts(
+    x_rf: &Vector,
+    axes_rf: &[Vector; 3],
+    h_rf: &[f64; 3],
+    face: usize,
+    n: &Vector,
+    x_inc: &Vector,
+    axes_inc: &[Vector; 3],
+    h_inc: &[f64; 3],
+) -> Vec<ContactPoint> {
+    // incident face is the one most anti-parallel to the reference normal
+    let k = (0..3)
+        .max_by(|&i, &j| {
+            axes_inc[i]
+                .dot(n)
+                .abs()
+                .total_cmp(&axes_inc[j].dot(n).abs())
+        })
+        .unwrap();
+    let sign = -axes_inc[k].dot(n).signum();
+    let center = x_inc + &(&axes_inc[k] * (sign * h_inc[k]));
+    let (u, v) = ((k + 1) % 3, (k + 2) % 3);
+    let du = &axes_inc[u] * h_inc[u];
+    let dv = &axes_inc[v] * h_inc[v];
+    let mut polygon = vec![
+        &(&center + &du) + &dv,
+        &(&center - &du) + &dv,
+        &(&center - &du) - &dv,
+        &(&center + &du) - &dv,
+    ];
+
+    for i in (0..3).filter(|&i| i != face) {
+        let offset = axes_rf[i].dot(x_rf);
+        polygon = clip(&polygon, &axes_rf[i], offset + h_rf[i]);
+        polygon = clip(&polygon, &-&axes_rf[i], -offset + h_rf[i]);
+    }
+
+    let face_offset = n.dot(x_rf) + h_rf[face];
+    polygon
+        .into_iter()
+        .filter_map(|point| {
+            let depth = face_offset - n.dot(&point);
+            if depth >= 0.0 {
+                Some(ContactPoint { point, depth })
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+// Keeps the part of `polygon` where `m . p <= d` (Sutherland-Hodgman)
+pub(super) fn clip(polygon: &[Vector], m: &Vector, d: f64) -> Vec<Vector> {
+    let mut result = Vec::with_capacity(polygon.len() + 1);
+    for (i, p) in polygon.iter().enumerate() {
+        let q = &polygon[(i + 1) % polygon.len()];
+        let dp = m.dot(p) - d;
+        let dq = m.dot(q) - d;
+        if dp <= 0.0 {
+            result.push(p.clone());
+        }
+        if (dp < 0.0 && dq > 0.0) || (dp > 0.0 && dq < 0.0) {
+            let s = dp / (dp - dq);
+            result.push(p + &(&(q - p) * s));
+        }
+    }
+    result
+}
+
+// Midpoint of the edge parallel to axis `i` furthest in direction `n`
+fn edge_point(x: &Vector, axes: &[Vector; 3], h: &[f64; 3], i: usize, n: &Vector) -> Vector {
+    (0..3).filter(|&k| k != i).fold(x.clone(), |p, k| {
+        p + &(&axes[k] * (h[k] * axes[k].dot(n).signum()))
+    })
+}
+
+// Closest points between the lines `pa + s da` and `pb + u db` (non-parallel)
+fn closest_points_on_lines(pa: &Vector, da: &Vector, pb: &Vector, db: &Vector) -> (Vector, Vector) {
+    let r = pa - pb;
+    let b = da.dot(db);
+    let c = da.dot(&r);
+    let f = db.dot(&r);
+    let denom = 1.0 - b * b;
+    let s = (b * f - c) / denom;
+    let u = (f - b * c) / denom;
+    (pa + &(da * s), pb + &(db * u))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::approx_eq::{assert_approx_eq, ApproxEq};
+    use crate::math::quaternion::Quaternion;
+    use std::f64::consts::PI;
+
+    fn state(x: Vector, q: Quaternion) -> RigidBodyState {
+        RigidBodyState::new(
+            &x,
+            &q,
+            &Vector::zero(),
+            &Vector::zero(),
+            1.0,
+            &Matrix::identity(),
+        )
+    }
+
+    fn unrotated() -> Quaternion {
+        Quaternion::coords(0.0, 0.0, 0.0, 1.0)
+    }
+
+    #[test]
+    fn test_separated_boxes() {
+        let half = Vector::new(0.5, 0.5, 0.5);
+        let a = state(Vector::zero(), unrotated());
+        let b = state(Vector::new(0.3, 0.2, 1.1), unrotated());
+        assert!(box_box(&a, &half, &b, &half).is_none());
+    }
+
+    #[test]
+    fn test_separated_along_edge_axis() {
+        // face axes overlap but the edges miss each other
+        let half = Vector::new(0.5, 0.5, 0.5);
+        let a = state(
+            Vector::zero(),
+            Quaternion::from_rotation(&Vector::new(1.0, 0.0, 0.0), PI / 4.0),
+        );
+        let b = state(
+            Vector::new(0.0, 0.0, 1.5),
+            Quaternion::from_rotation(&Vector::new(0.0, 1.0, 0.0), PI / 4.0),
+        );
+        assert!(box_box(&a, &half, &b, &half).is_none());
+    }
+
+    #[test]
+    fn test_face_face_contact() {
+        let half = Vector::new(0.5, 0.5, 0.5);
+        let a = state(Vector::zero(), unrotated());
+        let b = state(Vector::new(0.0, 0.0, 0.9), unrotated());
+        let manifold = box_box(&a, &half, &b, &half).unwrap();
+        assert_approx_eq!(manifold.normal, Vector::new(0.0, 0.0, 1.0));
+        assert_eq!(manifold.points.len(), 4);
+        for c in &manifold.points {
+            assert_approx_eq!(c.depth, 0.1);
+            assert_approx_eq!(c.point.x.abs(), 0.5);
+            assert_approx_eq!(c.point.y.abs(), 0.5);
+        }
+    }
+
+    #[test]
+    fn test_face_face_contact_with_offset_boxes() {
+        // the incident face is clipped to the reference face
+        let a = state(Vector::zero(), unrotated());
+        let b = state(Vector::new(0.8, 0.0, 0.0), unrotated());
+        let manifold = box_box(
+            &a,
+            &Vector::new(0.5, 0.5, 0.5),
+            &b,
+            &Vector::new(0.4, 1.0, 1.0),
+        )
+        .unwrap();
+        assert_approx_eq!(manifold.normal, Vector::new(1.0, 0.0, 0.0));
+        assert_eq!(manifold.points.len(), 4);
+        for c in &manifold.points {
+            assert_approx_eq!(c.depth, 0.1);
+            assert_approx_eq!(c.point.y.abs(), 0.5);
+            assert_approx_eq!(c.point.z.abs(), 0.5);
+        }
+    }
+
+    #[test]
+    fn test_edge_edge_contact() {
+        let half = Vector::new(0.5, 0.5, 0.5);
+        let reach = 0.5 * 2f64.sqrt();
+        let a = state(
+            Vector::zero(),
+            Quaternion::from_rotation(&Vector::new(1.0, 0.0, 0.0), PI / 4.0),
+        );
+        let b = state(
+            Vector::new(0.0, 0.0, 2.0 * reach - 0.1),
+            Quaternion::from_rotation(&Vector::new(0.0, 1.0, 0.0), PI / 4.0),
+        );
+        let manifold = box_box(&a, &half, &b, &half).unwrap();
+        assert_approx_eq!(manifold.normal, Vector::new(0.0, 0.0, 1.0));
+        assert_eq!(manifold.points.len(), 1);
+        assert_approx_eq!(manifold.points[0].depth, 0.1);
+        assert_approx_eq!(
+            manifold.points[0].point,
+            Vector::new(0.0, 0.0, reach - 0.05)
+        );
+    }
+
+    #[test]
+    fn test_vertex_face_contact() {
+        // B stands on a corner, its main diagonal aligned with the z axis
+        let half_a = Vector::new(2.0, 2.0, 0.5);
+        let half_b = Vector::new(0.5, 0.5, 0.5);
+        let reach = 0.5 * 3f64.sqrt();
+        let axis = Vector::new(1.0, -1.0, 0.0).normalize();
+        let angle = (1.0 / 3f64.sqrt()).acos();
+        let a = state(Vector::zero(), unrotated());
+        let b = state(
+            Vector::new(0.0, 0.0, 0.5 + reach - 0.1),
+            Quaternion::from_rotation(&axis, angle),
+        );
+        let manifold = box_box(&a, &half_a, &b, &half_b).unwrap();
+        assert_approx_eq!(manifold.normal, Vector::new(0.0, 0.0, 1.0));
+        assert_eq!(manifold.points.len(), 1);
+        assert_approx_eq!(manifold.points[0].depth, 0.1);
+        assert_approx_eq!(manifold.points[0].point, Vector::new(0.0, 0.0, 0.4));
+    }
+
+    #[test]
+    fn test_normal_points_from_a_to_b() {
+        let half = Vector::new(0.5, 0.5, 0.5);
+        let a = state(Vector::new(0.0, 0.95, 0.0), unrotated());
+        let b = state(Vector::zero(), unrotated());
+        let manifold = box_box(&a, &half, &b, &half).unwrap();
+        assert_approx_eq!(manifold.normal, Vector::new(0.0, -1.0, 0.0));
+    }
+}
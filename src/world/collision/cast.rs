@@ -0,0 +1,206 @@
+use super::gjk::{gjk, Gjk};
+use super::posed_parts;
+use super::support::{Posed, SupportMap};
+use crate::math::matrix::Matrix;
+use crate::math::vector::Vector;
+use crate::world::shape::Shape;
+
+const MAX_ITERATIONS: usize = 32;
+// distance below which conservative advancement has reached the surface
+const TOLERANCE: f64 = 1e-9;
+
+// Where a shape A moving along a unit direction first touches a shape B
+#[derive(Debug, Clone)]
+pub struct Impact {
+    // distance travelled
+    pub t: f64,
+    // outward normal of B at the contact, facing A
+    pub normal: Vector,
+    pub point: Vector,
+}
+
+pub(crate) struct Point<'a>(pub(crate) &'a Vector);
+
+impl SupportMap for Point<'_> {
+    fn support(&self, _: &Vector) -> Vector {
+        self.0.clone()
+    }
+}
+
+struct Translated<'a> {
+    shape: &'a dyn SupportMap,
+    offset: Vector,
+}
+
+impl SupportMap for Translated<'_> {
+    fn support(&self, direction: &Vector) -> Vector {
+        self.shape.support(direction) + &self.offset
+    }
+}
+
+// Conservative advancement: A steps along `direction` by its distance to B over the
+// speed at which it closes that distance, which never passes the surface of B.
+// Shapes overlapping from the start touch at once.
+pub(crate) fn time_of_impact(
+    a: &dyn SupportMap,
+    direction: &Vector,
+    max_t: f64,
+    b: &dyn SupportMap,
+) -> Option<Impact> {
+    let mut t = 0.0;
+    let mut normal = -direction;
+    let mut point = None;
+    for _ in 0..MAX_ITERATIONS {
+        let moved = Translated {
+            shape: a,
+            offset: direction * t,
+        };
+        let Gjk::Separated {
+            distance,
+            point_a,
+            point_b,
+        } = gjk(&moved, b)
+        else {
+            break;
+        };
+        if distance <= TOLERANCE {
+            point = Some(point_b);
+            break;
+        }
+        let towards_b = &(&point_b - &point_a) / distance;
+        let approach = towards_b.dot(direction);
+        if approach <= 0.0 {
+            return None;
+        }
+        normal = -towards_b;
+        // B stays put, so its closest point is the best guess at the contact
+        point = Some(point_b);
+        t += distance / approach;
+        if t > max_t {
+            return None;
+        }
+    }
+    let point = point.unwrap_or_else(|| a.support(direction));
+    Some(Impact { t, normal, point })
+}
+
+// Shape A at `xa` with orientation `ra` swept along the unit `direction` for up to
+// `max_t` against shape B. Compounds are swept part by part, meshes as their hulls.
+#[allow(clippy::too_many_arguments)]
+pub fn cast_shape(
+    xa: &Vector,
+    ra: &Matrix,
+    a: &Shape,
+    direction: &Vector,
+    max_t: f64,
+    xb: &Vector,
+    rb: &Matrix,
+    b: &Shape,
+) -> Option<Impact> {
+    let parts_b = posed_parts(xb, rb, b);
+    let mut nearest: Option<Impact> = None;
+    for (xa, ra, part_a) in posed_parts(xa, ra, a) {
+        for (xb, rb, part_b) in &parts_b {
+            let posed_a = Posed {
+                x: &xa,
+                r: &ra,
+                shape: part_a,
+            };
+            let posed_b = Posed {
+                x: xb,
+                r: rb,
+                shape: part_b,
+            };
+            let max_t = nearest.as_ref().map_or(max_t, |impact| impact.t);
+            if let Some(impact) = time_of_impact(&posed_a, direction, max_t, &posed_b) {
+                nearest = Some(impact);
+            }
+        }
+    }
+    nearest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::approx_eq::{assert_approx_eq, ApproxEq};
+
+    fn unit_box() -> Shape {
+        Shape::Box {
+            half_extents: Vector::new(0.5, 0.5, 0.5),
+        }
+    }
+
+    #[test]
+    fn test_box_swept_onto_box() {
+        let identity = Matrix::identity();
+        let impact = cast_shape(
+            &Vector::new(-5.0, 0.2, 0.0),
+            &identity,
+            &unit_box(),
+            &Vector::new(1.0, 0.0, 0.0),
+            10.0,
+            &Vector::zero(),
+            &identity,
+            &unit_box(),
+        )
+        .unwrap();
+        assert!((impact.t - 4.0).abs() < 1e-6, "t = {}", impact.t);
+        assert_approx_eq!(impact.normal, Vector::new(-1.0, 0.0, 0.0));
+        assert!((impact.point.x + 0.5).abs() < 1e-6);
+        let short = cast_shape(
+            &Vector::new(-5.0, 0.2, 0.0),
+            &identity,
+            &unit_box(),
+            &Vector::new(1.0, 0.0, 0.0),
+            3.5,
+            &Vector::zero(),
+            &identity,
+            &unit_box(),
+        );
+        assert!(short.is_none());
+    }
+
+    #[test]
+    fn test_sphere_swept_past_and_onto_capsule() {
+        let identity = Matrix::identity();
+        let sphere = Shape::Sphere { radius: 0.5 };
+        let capsule = Shape::Capsule {
+            radius: 0.5,
+            half_height: 1.0,
+        };
+        let sweep = |start: Vector| {
+            cast_shape(
+                &start,
+                &identity,
+                &sphere,
+                &Vector::new(0.0, 0.0, -1.0),
+                10.0,
+                &Vector::zero(),
+                &identity,
+                &capsule,
+            )
+        };
+        // lands on the top cap
+        let impact = sweep(Vector::new(0.0, 0.0, 5.0)).unwrap();
+        assert!((impact.t - 3.0).abs() < 1e-4, "t = {}", impact.t);
+        assert!(impact.normal.z > 0.99);
+        assert!(sweep(Vector::new(1.1, 0.0, 5.0)).is_none());
+    }
+
+    #[test]
+    fn test_overlapping_shapes_touch_at_once() {
+        let identity = Matrix::identity();
+        let impact = cast_shape(
+            &Vector::new(0.5, 0.0, 0.0),
+            &identity,
+            &unit_box(),
+            &Vector::new(1.0, 0.0, 0.0),
+            10.0,
+            &Vector::zero(),
+            &identity,
+            &unit_box(),
+        );
+        assert_approx_eq!(impact.unwrap().t, 0.0);
+    }
+}
@@ -0,0 +1,250 @@
+use super::box_box::clip;
+use super::epa::epa;
+use super::gjk::{gjk, Gjk};
+use super::support::{Posed, PosedCore, SupportMap};
+use super::{ContactManifold, ContactPoint};
+use crate::math::matrix::Matrix;
+use crate::math::vector::Vector;
+use crate::world::shape::Shape;
+use std::f64::consts::PI;
+
+// The face, edge or vertex of a shape at a contact is made of its support points
+// in directions tilted this far from the normal, in this many directions around it
+const FEATURE_TILT: f64 = 0.05;
+const FEATURE_DIRECTIONS: usize = 8;
+// Feature points closer than this are merged
+const MERGE_DISTANCE: f64 = 1e-9;
+
+// Contact between any two convex shapes (A and B). The cores of rounded shapes are
+// compared by GJK, so EPA is only needed once the cores overlap. Where one shape
+// meets the other with a face, the other's feature is clipped against that face
+// as for boxes, so that resting shapes get several points to stand on.
+pub fn convex_convex(
+    xa: &Vector,
+    ra: &Matrix,
+    shape_a: &Shape,
+    xb: &Vector,
+    rb: &Matrix,
+    shape_b: &Shape,
+) -> Option<ContactManifold> {
+    let a = Posed {
+        x: xa,
+        r: ra,
+        shape: shape_a,
+    };
+    let b = Posed {
+        x: xb,
+        r: rb,
+        shape: shape_b,
+    };
+    let margin = shape_a.margin() + shape_b.margin();
+    let (core_a, core_b) = (PosedCore(a), PosedCore(b));
+    let (normal, depth, point_a, point_b) = match gjk(&core_a, &core_b) {
+        Gjk::Separated {
+            distance,
+            point_a,
+            point_b,
+        } => {
+            if distance >= margin || distance == 0.0 {
+                return None;
+            }
+            let normal = &(&point_b - &point_a) / distance;
+            let point_a = &point_a + &(&normal * shape_a.margin());
+            let point_b = &point_b - &(&normal * shape_b.margin());
+            (normal, margin - distance, point_a, point_b)
+        }
+        Gjk::Intersecting(_) => {
+            let (a, b) = (core_a.0, core_b.0);
+            let simplex = match gjk(&a, &b) {
+                Gjk::Intersecting(simplex) => simplex,
+                Gjk::Separated { .. } => return None,
+            };
+            let p = epa(&a, &b, &simplex)?;
+            (p.normal, p.depth, p.point_a, p.point_b)
+        }
+    };
+    let points = clip_features(
+        &PosedCore(Posed {
+            x: xa,
+            r: ra,
+            shape: shape_a,
+        }),
+        shape_a.margin(),
+        &PosedCore(Posed {
+            x: xb,
+            r: rb,
+            shape: shape_b,
+        }),
+        shape_b.margin(),
+        &normal,
+    )
+    .unwrap_or_else(|| {
+        vec![ContactPoint {
+            point: &(&point_a + &point_b) * 0.5,
+            depth,
+        }]
+    });
+    Some(ContactManifold { normal, points })
+}
+
+// Clips the feature of B against that of A, or the other way round, whichever has
+// the larger face. None when neither has a face or the clipping leaves nothing.
+fn clip_features(
+    a: &PosedCore,
+    margin_a: f64,
+    b: &PosedCore,
+    margin_b: f64,
+    n: &Vector,
+) -> Option<Vec<ContactPoint>> {
+    let feature_a = feature(a, margin_a, n);
+    let feature_b = feature(b, margin_b, &-n);
+    let (reference, incident, n) = if area(&feature_b, n) > area(&feature_a, n) {
+        (feature_b, feature_a, -n)
+    } else {
+        (feature_a, feature_b, n.clone())
+    };
+    if reference.len() < 3 {
+        return None;
+    }
+    let center = centroid(&reference);
+    let mut polygon = incident;
+    for (i, p) in reference.iter().enumerate() {
+        let q = &reference[(i + 1) % reference.len()];
+        // outward normal of the side plane through the edge
+        let mut m = (q - p).cross(&n);
+        if m.dot(&(&center - p)) > 0.0 {
+            m = -m;
+        }
+        polygon = clip(&polygon, &m, m.dot(p));
+    }
+    let height = reference
+        .iter()
+        .map(|p| n.dot(p))
+        .fold(f64::NEG_INFINITY, f64::max);
+    let mut points: Vec<ContactPoint> = Vec::new();
+    for q in polygon {
+        let depth = height - n.dot(&q);
+        let merged = points
+            .iter()
+            .any(|c| (&c.point - &q).magnitude() < MERGE_DISTANCE);
+        if depth >= 0.0 && !merged {
+            // halfway between the surfaces, like the single point
+            points.push(ContactPoint {
+                point: q + &(&n * (0.5 * depth)),
+                depth,
+            });
+        }
+    }
+    (!points.is_empty()).then_some(points)
+}
+
+// Face, edge or vertex of a shape furthest along the unit vector `n`, as points
+// in order around `n`
+fn feature(core: &PosedCore, margin: f64, n: &Vector) -> Vec<Vector> {
+    let (t, u) = n.orthonormal_basis();
+    let mut points: Vec<Vector> = Vec::new();
+    for k in 0..FEATURE_DIRECTIONS {
+        let angle = 2.0 * PI * k as f64 / FEATURE_DIRECTIONS as f64;
+        let tilt = &(&t * angle.cos()) + &(&u * angle.sin());
+        let p = core.support(&(n + &(&tilt * FEATURE_TILT))) + &(n * margin);
+        if points
+            .iter()
+            .all(|q| (q - &p).magnitude() >= MERGE_DISTANCE)
+        {
+            points.push(p);
+        }
+    }
+    let center = centroid(&points);
+    let angle = |p: &Vector| {
+        let d = p - &center;
+        d.dot(&u).atan2(d.dot(&t))
+    };
+    points.sort_by(|p, q| angle(p).total_cmp(&angle(q)));
+    points
+}
+
+fn centroid(points: &[Vector]) -> Vector {
+    &points.iter().fold(Vector::zero(), |sum, p| sum + p) / points.len() as f64
+}
+
+// Area of a polygon projected along `n`, zero for points and segments
+fn area(polygon: &[Vector], n: &Vector) -> f64 {
+    let twice: f64 = (0..polygon.len())
+        .map(|i| polygon[i].cross(&polygon[(i + 1) % polygon.len()]).dot(n))
+        .sum();
+    0.5 * twice.abs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::approx_eq::{assert_approx_eq, ApproxEq};
+
+    #[test]
+    fn test_sphere_on_box() {
+        let r = Matrix::identity();
+        let cuboid = Shape::Box {
+            half_extents: Vector::new(1.0, 1.0, 1.0),
+        };
+        let sphere = Shape::Sphere { radius: 0.5 };
+        let xb = Vector::new(0.2, 0.0, 1.4);
+        let m = convex_convex(&Vector::zero(), &r, &cuboid, &xb, &r, &sphere).unwrap();
+        assert_approx_eq!(m.normal, Vector::new(0.0, 0.0, 1.0));
+        assert_approx_eq!(m.points[0].depth, 0.1);
+        assert_approx_eq!(m.points[0].point, Vector::new(0.2, 0.0, 0.95));
+        let apart = Vector::new(0.2, 0.0, 1.6);
+        assert!(convex_convex(&Vector::zero(), &r, &cuboid, &apart, &r, &sphere).is_none());
+    }
+
+    #[test]
+    fn test_crossed_capsules() {
+        let r = Matrix::identity();
+        let lying = crate::math::quaternion::Quaternion::from_rotation(
+            &Vector::new(1.0, 0.0, 0.0),
+            std::f64::consts::FRAC_PI_2,
+        )
+        .to_rotation_matrix();
+        let capsule = Shape::Capsule {
+            radius: 0.5,
+            half_height: 1.0,
+        };
+        let xb = Vector::new(0.8, 0.0, 0.3);
+        let m = convex_convex(&Vector::zero(), &r, &capsule, &xb, &lying, &capsule).unwrap();
+        assert_approx_eq!(m.normal, Vector::new(1.0, 0.0, 0.0));
+        assert_approx_eq!(m.points[0].depth, 0.2);
+    }
+
+    #[test]
+    fn test_deep_overlap_uses_epa() {
+        let r = Matrix::identity();
+        let sphere = Shape::Sphere { radius: 1.0 };
+        let cuboid = Shape::Box {
+            half_extents: Vector::new(1.0, 1.0, 1.0),
+        };
+        let xb = Vector::new(0.0, 0.0, 0.5);
+        let m = convex_convex(&Vector::zero(), &r, &cuboid, &xb, &r, &sphere).unwrap();
+        assert!((&m.normal - &Vector::new(0.0, 0.0, 1.0)).magnitude() < 1e-2);
+        assert!((m.points[0].depth - 1.5).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_cylinder_on_box_touches_around_its_cap() {
+        let r = Matrix::identity();
+        let cuboid = Shape::Box {
+            half_extents: Vector::new(2.0, 2.0, 0.5),
+        };
+        let cylinder = Shape::Cylinder {
+            radius: 0.5,
+            half_height: 0.5,
+        };
+        let xb = Vector::new(0.3, 0.0, 0.95);
+        let m = convex_convex(&Vector::zero(), &r, &cuboid, &xb, &r, &cylinder).unwrap();
+        assert_approx_eq!(m.normal, Vector::new(0.0, 0.0, 1.0));
+        assert!(m.points.len() >= 3, "{} points", m.points.len());
+        for p in &m.points {
+            assert_approx_eq!(p.depth, 0.05);
+            assert_approx_eq!(p.point.z, 0.475);
+            assert!((p.point.x - 0.3).hypot(p.point.y) < 0.5 + 1e-9);
+        }
+    }
+}
@@ -0,0 +1,248 @@
+use super::gjk::{combine, SupportPoint};
+use super::support::SupportMap;
+use crate::math::vector::Vector;
+
+const MAX_ITERATIONS: usize = 64;
+const TOLERANCE: f64 = 1e-6;
+
+// Smallest translation separating two overlapping convex sets
+#[derive(Debug)]
+pub struct Penetration {
+    // points from A towards B
+    pub normal: Vector,
+    pub depth: f64,
+    // deepest points of A inside B and of B inside A
+    pub point_a: Vector,
+    pub point_b: Vector,
+}
+
+struct Face {
+    indices: [usize; 3],
+    normal: Vector,
+    distance: f64,
+}
+
+impl Face {
+    fn new(points: &[SupportPoint], indices: [usize; 3]) -> Option<Self> {
+        let [a, b, c] = indices.map(|i| &points[i].w);
+        let normal = (b - a).cross(&(c - a));
+        let length = normal.magnitude();
+        if length <= f64::EPSILON {
+            return None;
+        }
+        let normal = &normal / length;
+        Some(Self {
+            distance: normal.dot(a),
+            indices,
+            normal,
+        })
+    }
+}
+
+// Expanding polytope algorithm, starting from a GJK simplex containing the origin
+pub fn epa(
+    a: &dyn SupportMap,
+    b: &dyn SupportMap,
+    simplex: &[SupportPoint],
+) -> Option<Penetration> {
+    let mut points = simplex.to_vec();
+    if !fill_tetrahedron(a, b, &mut points) {
+        return None;
+    }
+    // wind the faces of the tetrahedron outwards
+    let center = &points.iter().fold(Vector::zero(), |sum, p| sum + &p.w) / 4.0;
+    let mut faces = Vec::new();
+    for [i, j, k] in [[0, 1, 2], [0, 3, 1], [0, 2, 3], [1, 3, 2]] {
+        let outward = (&points[j].w - &points[i].w)
+            .cross(&(&points[k].w - &points[i].w))
+            .dot(&(&points[i].w - &center))
+            >= 0.0;
+        faces.push(Face::new(
+            &points,
+            if outward { [i, j, k] } else { [i, k, j] },
+        )?);
+    }
+    for _ in 0..MAX_ITERATIONS {
+        let closest = (0..faces.len())
+            .min_by(|&i, &j| faces[i].distance.total_cmp(&faces[j].distance))
+            .unwrap();
+        let face = &faces[closest];
+        let next = SupportPoint::new(a, b, &face.normal);
+        if next.w.dot(&face.normal) - face.distance <= TOLERANCE {
+            return Some(penetration(&points, face));
+        }
+        points.push(next);
+        let new = points.len() - 1;
+        // remove the faces the new point sees, keeping the boundary of the hole
+        let mut horizon: Vec<(usize, usize)> = Vec::new();
+        faces.retain(|face| {
+            let visible = face
+                .normal
+                .dot(&(&points[new].w - &points[face.indices[0]].w))
+                > 0.0;
+            if visible {
+                let [i, j, k] = face.indices;
+                for (p, q) in [(i, j), (j, k), (k, i)] {
+                    match horizon.iter().position(|&edge| edge == (q, p)) {
+                        Some(shared) => {
+                            horizon.swap_remove(shared);
+                        }
+                        None => horizon.push((p, q)),
+                    }
+                }
+            }
+            !visible
+        });
+        for (p, q) in horizon {
+            if let Some(face) = Face::new(&points, [p, q, new]) {
+                faces.push(face);
+            }
+        }
+        if faces.is_empty() {
+            return None;
+        }
+    }
+    let closest = faces
+        .iter()
+        .min_by(|f, g| f.distance.total_cmp(&g.distance))?;
+    Some(penetration(&points, closest))
+}
+
+fn penetration(points: &[SupportPoint], face: &Face) -> Penetration {
+    // barycentric coordinates of the origin projected onto the face
+    let [a, b, c] = face.indices.map(|i| &points[i].w);
+    let p = &face.normal * face.distance;
+    let area = |u: &Vector, v: &Vector, w: &Vector| (v - u).cross(&(w - u)).dot(&face.normal);
+    let total = area(a, b, c);
+    let weights = [
+        area(&p, b, c) / total,
+        area(a, &p, c) / total,
+        area(a, b, &p) / total,
+    ];
+    let corners: Vec<SupportPoint> = face.indices.iter().map(|&i| points[i].clone()).collect();
+    Penetration {
+        normal: face.normal.clone(),
+        depth: face.distance.max(0.0),
+        point_a: combine(&corners, &weights, |p| &p.a),
+        point_b: combine(&corners, &weights, |p| &p.b),
+    }
+}
+
+// Adds support points until the simplex is a tetrahedron with volume
+fn fill_tetrahedron(
+    a: &dyn SupportMap,
+    b: &dyn SupportMap,
+    points: &mut Vec<SupportPoint>,
+) -> bool {
+    while points.len() < 4 {
+        let w0 = points[0].w.clone();
+        let directions = match points.len() {
+            1 => vec![
+                Vector::new(1.0, 0.0, 0.0),
+                Vector::new(-1.0, 0.0, 0.0),
+                Vector::new(0.0, 1.0, 0.0),
+                Vector::new(0.0, -1.0, 0.0),
+                Vector::new(0.0, 0.0, 1.0),
+                Vector::new(0.0, 0.0, -1.0),
+            ],
+            2 => {
+                let (u, v) = (&points[1].w - &w0).normalize().orthonormal_basis();
+                vec![u.clone(), -&u, v.clone(), -&v]
+            }
+            _ => {
+                let n = (&points[1].w - &w0).cross(&(&points[2].w - &w0));
+                vec![n.clone(), -&n]
+            }
+        };
+        let spans = |w: &Vector| match points.len() {
+            1 => (w - &w0).magnitude() > TOLERANCE,
+            2 => (&points[1].w - &w0).cross(&(w - &w0)).magnitude() > TOLERANCE,
+            _ => {
+                let n = (&points[1].w - &w0).cross(&(&points[2].w - &w0));
+                n.dot(&(w - &w0)).abs() > TOLERANCE * n.magnitude()
+            }
+        };
+        match directions
+            .iter()
+            .map(|d| SupportPoint::new(a, b, d))
+            .find(|p| spans(&p.w))
+        {
+            Some(point) => points.push(point),
+            None => return false,
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::approx_eq::{assert_approx_eq, ApproxEq};
+    use crate::math::matrix::Matrix;
+    use crate::math::quaternion::Quaternion;
+    use crate::world::collision::box_box::box_box_posed;
+    use crate::world::collision::gjk::{gjk, Gjk};
+    use crate::world::collision::support::Posed;
+    use crate::world::shape::Shape;
+
+    fn penetrate(a: &Posed, b: &Posed) -> Penetration {
+        match gjk(a, b) {
+            Gjk::Intersecting(simplex) => epa(a, b, &simplex).unwrap(),
+            Gjk::Separated { .. } => panic!("shapes are apart"),
+        }
+    }
+
+    #[test]
+    fn test_overlapping_spheres() {
+        let r = Matrix::identity();
+        let (xa, xb) = (Vector::zero(), Vector::new(0.0, 1.5, 0.0));
+        let sphere = Shape::Sphere { radius: 1.0 };
+        let a = Posed {
+            x: &xa,
+            r: &r,
+            shape: &sphere,
+        };
+        let b = Posed {
+            x: &xb,
+            r: &r,
+            shape: &sphere,
+        };
+        let p = penetrate(&a, &b);
+        assert!((p.depth - 0.5).abs() < 1e-3, "depth = {}", p.depth);
+        assert!((&p.normal - &Vector::new(0.0, 1.0, 0.0)).magnitude() < 1e-2);
+        assert!((p.point_a.y - 1.0).abs() < 1e-3 && (p.point_b.y - 0.5).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_box_depth_matches_separating_axes() {
+        let ra = Quaternion::from_rotation(&Vector::new(0.0, 0.0, 1.0), 0.4).to_rotation_matrix();
+        let rb = Quaternion::from_rotation(&Vector::new(1.0, 1.0, 0.0).normalize(), 0.3)
+            .to_rotation_matrix();
+        let (xa, xb) = (Vector::zero(), Vector::new(0.3, 0.2, 0.9));
+        let half = Vector::new(0.5, 0.5, 0.5);
+        let shape = Shape::Box {
+            half_extents: half.clone(),
+        };
+        let a = Posed {
+            x: &xa,
+            r: &ra,
+            shape: &shape,
+        };
+        let b = Posed {
+            x: &xb,
+            r: &rb,
+            shape: &shape,
+        };
+        let p = penetrate(&a, &b);
+        let sat = box_box_posed(&xa, &ra, &half, &xb, &rb, &half).unwrap();
+        let deepest = sat.points.iter().map(|p| p.depth).fold(0.0, f64::max);
+        assert!(
+            (p.depth - deepest).abs() < 1e-4,
+            "{} vs {}",
+            p.depth,
+            deepest
+        );
+        assert_approx_eq!(p.normal.dot(&sat.normal), 1.0);
+        assert!((&(&p.point_a - &p.point_b) - &(&p.normal * p.depth)).magnitude() < 1e-6);
+    }
+}
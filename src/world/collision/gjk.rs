@@ -0,0 +1,248 @@
+use super::support::SupportMap;
+use crate::math::vector::Vector;
+
+const MAX_ITERATIONS: usize = 64;
+// relative tolerance on the squared distance
+const TOLERANCE: f64 = 1e-10;
+
+// Point of the Minkowski difference A - B with the points of A and B it came from
+#[derive(Debug, Clone)]
+pub struct SupportPoint {
+    pub w: Vector,
+    pub a: Vector,
+    pub b: Vector,
+}
+
+impl SupportPoint {
+    // Furthest point of A - B along `direction`
+    pub fn new(a: &dyn SupportMap, b: &dyn SupportMap, direction: &Vector) -> Self {
+        let a = a.support(direction);
+        let b = b.support(&-direction);
+        Self { w: &a - &b, a, b }
+    }
+}
+
+#[derive(Debug)]
+pub enum Gjk {
+    // closest points of A and B
+    Separated {
+        distance: f64,
+        point_a: Vector,
+        point_b: Vector,
+    },
+    // final simplex, which contains the origin, to start EPA from
+    Intersecting(Vec<SupportPoint>),
+}
+
+// Distance between convex sets A and B by the Gilbert-Johnson-Keerthi algorithm
+pub fn gjk(a: &dyn SupportMap, b: &dyn SupportMap) -> Gjk {
+    let mut simplex = vec![SupportPoint::new(a, b, &Vector::new(1.0, 0.0, 0.0))];
+    let mut weights = vec![1.0];
+    for _ in 0..MAX_ITERATIONS {
+        let v = combine(&simplex, &weights, |p| &p.w);
+        let vv = v.dot(&v);
+        if vv <= TOLERANCE * max_norm(&simplex) {
+            return Gjk::Intersecting(simplex);
+        }
+        let next = SupportPoint::new(a, b, &-&v);
+        // no progress towards the origin
+        if vv - v.dot(&next.w) <= TOLERANCE * vv
+            || simplex
+                .iter()
+                .any(|p| (&p.w - &next.w).dot(&(&p.w - &next.w)) <= TOLERANCE * vv)
+        {
+            break;
+        }
+        simplex.push(next);
+        let (kept, kept_weights) = closest_on_simplex(&simplex);
+        simplex = kept;
+        weights = kept_weights;
+        if simplex.len() == 4 {
+            return Gjk::Intersecting(simplex);
+        }
+    }
+    let point_a = combine(&simplex, &weights, |p| &p.a);
+    let point_b = combine(&simplex, &weights, |p| &p.b);
+    Gjk::Separated {
+        distance: (&point_a - &point_b).magnitude(),
+        point_a,
+        point_b,
+    }
+}
+
+fn max_norm(simplex: &[SupportPoint]) -> f64 {
+    simplex.iter().map(|p| p.w.dot(&p.w)).fold(1.0, f64::max)
+}
+
+pub(crate) fn combine<'a>(
+    simplex: &'a [SupportPoint],
+    weights: &[f64],
+    f: impl Fn(&'a SupportPoint) -> &'a Vector,
+) -> Vector {
+    simplex
+        .iter()
+        .zip(weights)
+        .fold(Vector::zero(), |sum, (p, weight)| sum + &(f(p) * *weight))
+}
+
+// Smallest face of the simplex holding the point closest to the origin, with the
+// barycentric weights of that point. A tetrahedron is kept whole only when it
+// contains the origin.
+fn closest_on_simplex(simplex: &[SupportPoint]) -> (Vec<SupportPoint>, Vec<f64>) {
+    let mut best: Option<(f64, usize, Vec<f64>)> = None;
+    for subset in 1..(1usize << simplex.len()) {
+        let points: Vec<&Vector> = (0..simplex.len())
+            .filter(|i| subset & (1 << i) != 0)
+            .map(|i| &simplex[i].w)
+            .collect();
+        let Some(weights) = affine_closest(&points) else {
+            continue;
+        };
+        if weights.iter().any(|&w| w <= 0.0) {
+            continue;
+        }
+        let closest = points
+            .iter()
+            .zip(&weights)
+            .fold(Vector::zero(), |sum, (p, w)| sum + &(*p * *w));
+        let distance = closest.dot(&closest);
+        if best.as_ref().is_none_or(|(d, _, _)| distance < *d) {
+            best = Some((distance, subset, weights));
+        }
+    }
+    let (_, subset, weights) = best.unwrap();
+    let kept = (0..simplex.len())
+        .filter(|i| subset & (1 << i) != 0)
+        .map(|i| simplex[i].clone())
+        .collect();
+    (kept, weights)
+}
+
+// Barycentric weights of the point closest to the origin on the affine hull of
+// `points`, None when the points are affinely dependent
+fn affine_closest(points: &[&Vector]) -> Option<Vec<f64>> {
+    let n = points.len() - 1;
+    let edges: Vec<Vector> = points[1..].iter().map(|p| *p - points[0]).collect();
+    // normal equations for p = y0 + sum mu_i (y_i - y0)
+    let mut m = [[0.0; 4]; 3];
+    for i in 0..n {
+        for j in 0..n {
+            m[i][j] = edges[i].dot(&edges[j]);
+        }
+        m[i][n] = -edges[i].dot(points[0]);
+    }
+    let scale = edges.iter().map(|e| e.dot(e)).fold(0.0, f64::max);
+    // Gaussian elimination with partial pivoting
+    for col in 0..n {
+        let pivot = (col..n).max_by(|&i, &j| m[i][col].abs().total_cmp(&m[j][col].abs()))?;
+        if m[pivot][col].abs() <= 1e-12 * scale * scale {
+            return None;
+        }
+        m.swap(col, pivot);
+        for row in 0..n {
+            if row != col {
+                let factor = m[row][col] / m[col][col];
+                let pivot_row = m[col];
+                for (value, p) in m[row].iter_mut().zip(pivot_row) {
+                    *value -= factor * p;
+                }
+            }
+        }
+    }
+    let mu: Vec<f64> = (0..n).map(|i| m[i][n] / m[i][i]).collect();
+    let mut weights = vec![1.0 - mu.iter().sum::<f64>()];
+    weights.extend(mu);
+    Some(weights)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::approx_eq::{assert_approx_eq, ApproxEq};
+    use crate::math::matrix::Matrix;
+    use crate::math::quaternion::Quaternion;
+    use crate::world::collision::support::Posed;
+    use crate::world::shape::Shape;
+
+    fn unit_box() -> Shape {
+        Shape::Box {
+            half_extents: Vector::new(0.5, 0.5, 0.5),
+        }
+    }
+
+    #[test]
+    fn test_distance_between_boxes() {
+        let r = Matrix::identity();
+        let (xa, xb) = (Vector::zero(), Vector::new(3.0, 0.2, -0.1));
+        let shape = unit_box();
+        let a = Posed {
+            x: &xa,
+            r: &r,
+            shape: &shape,
+        };
+        let b = Posed {
+            x: &xb,
+            r: &r,
+            shape: &shape,
+        };
+        match gjk(&a, &b) {
+            Gjk::Separated {
+                distance,
+                point_a,
+                point_b,
+            } => {
+                assert_approx_eq!(distance, 2.0);
+                assert_approx_eq!(point_a.x, 0.5);
+                assert_approx_eq!(point_b.x, 2.5);
+            }
+            Gjk::Intersecting(_) => panic!("boxes are apart"),
+        }
+    }
+
+    #[test]
+    fn test_distance_from_corner_to_sphere() {
+        let r = Quaternion::from_rotation(&Vector::new(0.0, 0.0, 1.0), 0.3).to_rotation_matrix();
+        let identity = Matrix::identity();
+        let (xa, xb) = (Vector::zero(), Vector::new(2.0, 2.0, 2.0));
+        let shape = unit_box();
+        let sphere = Shape::Sphere { radius: 0.5 };
+        let a = Posed {
+            x: &xa,
+            r: &identity,
+            shape: &shape,
+        };
+        let b = Posed {
+            x: &xb,
+            r: &r,
+            shape: &sphere,
+        };
+        match gjk(&a, &b) {
+            Gjk::Separated {
+                distance, point_a, ..
+            } => {
+                let corner = Vector::new(0.5, 0.5, 0.5);
+                assert_approx_eq!(point_a, corner);
+                assert!((distance - ((&xb - &corner).magnitude() - 0.5)).abs() < 1e-6);
+            }
+            Gjk::Intersecting(_) => panic!("shapes are apart"),
+        }
+    }
+
+    #[test]
+    fn test_overlapping_boxes_intersect() {
+        let r = Quaternion::from_rotation(&Vector::new(1.0, 0.0, 0.0), 0.7).to_rotation_matrix();
+        let (xa, xb) = (Vector::zero(), Vector::new(0.6, 0.3, 0.1));
+        let shape = unit_box();
+        let a = Posed {
+            x: &xa,
+            r: &r,
+            shape: &shape,
+        };
+        let b = Posed {
+            x: &xb,
+            r: &r,
+            shape: &shape,
+        };
+        assert!(matches!(gjk(&a, &b), Gjk::Intersecting(_)));
+    }
+}
@@ -0,0 +1,86 @@
+use self::box_box::box_box_posed;
+use self::convex::convex_convex;
+use crate::math::matrix::Matrix;
+use crate::math::vector::Vector;
+use crate::world::shape::Shape;
+
+pub mod box_box;
+pub mod cast;
+pub mod convex;
+pub mod epa;
+pub mod gjk;
+pub mod plane;
+pub mod ray;
+pub mod support;
+
+#[derive(Debug, Clone)]
+pub struct ContactPoint {
+    // world position of the contact
+    pub point: Vector,
+    // penetration depth along the manifold normal
+    pub depth: f64,
+}
+
+// Contacts between two shapes A and B sharing one normal, which points from A towards B
+#[derive(Debug, Clone)]
+pub struct ContactManifold {
+    pub normal: Vector,
+    pub points: Vec<ContactPoint>,
+}
+
+// Corners of a box with center `x`, orientation `r` and the given half extents
+pub(crate) fn box_vertices(x: &Vector, r: &Matrix, half_extents: &Vector) -> Vec<Vector> {
+    let mut vertices = Vec::with_capacity(8);
+    for sx in [-1.0, 1.0] {
+        for sy in [-1.0, 1.0] {
+            for sz in [-1.0, 1.0] {
+                let local = Vector::new(
+                    sx * half_extents.x,
+                    sy * half_extents.y,
+                    sz * half_extents.z,
+                );
+                vertices.push(x + &(r * &local));
+            }
+        }
+    }
+    vertices
+}
+
+// Leaf shapes of `shape` placed at `x` with orientation `r`, with compounds flattened
+pub(crate) fn posed_parts<'a>(
+    x: &Vector,
+    r: &Matrix,
+    shape: &'a Shape,
+) -> Vec<(Vector, Matrix, &'a Shape)> {
+    match shape {
+        Shape::Compound { children } => children
+            .iter()
+            .flat_map(|child| {
+                let child_r = r * &child.orientation.to_rotation_matrix();
+                posed_parts(&(x + &(r * &child.position)), &child_r, &child.shape)
+            })
+            .collect(),
+        _ => vec![(x.clone(), r.clone(), shape)],
+    }
+}
+
+// Contacts between two shapes which are not compounds: boxes by separating axes,
+// everything else by GJK and EPA
+pub fn shape_shape(
+    xa: &Vector,
+    ra: &Matrix,
+    a: &Shape,
+    xb: &Vector,
+    rb: &Matrix,
+    b: &Shape,
+) -> Option<ContactManifold> {
+    match (a, b) {
+        (
+            Shape::Box { half_extents },
+            Shape::Box {
+                half_extents: half_b,
+            },
+        ) => box_box_posed(xa, ra, half_extents, xb, rb, half_b),
+        _ => convex_convex(xa, ra, a, xb, rb, b),
+    }
+}
@@ -0,0 +1,200 @@
+use super::{box_vertices, posed_parts, ContactManifold, ContactPoint};
+use crate::math::matrix::Matrix;
+use crate::math::vector::Vector;
+use crate::world::rigid_body_state::RigidBodyState;
+use crate::world::shape::Shape;
+
+// Contacts between the plane `normal . p = offset` (A) and a shape (B)
+pub fn plane_shape(
+    normal: &Vector,
+    offset: f64,
+    state: &RigidBodyState,
+    shape: &Shape,
+) -> Option<ContactManifold> {
+    plane_shape_posed(normal, offset, &state.x, &state.r, shape)
+}
+
+pub(crate) fn plane_shape_posed(
+    normal: &Vector,
+    offset: f64,
+    x: &Vector,
+    r: &Matrix,
+    shape: &Shape,
+) -> Option<ContactManifold> {
+    // the shape is the set of points within `radius` of the candidates
+    let (candidates, radius) = match shape {
+        Shape::Box { half_extents } => (box_vertices(x, r, half_extents), 0.0),
+        Shape::Sphere { radius } => (vec![x.clone()], *radius),
+        Shape::Capsule {
+            radius,
+            half_height,
+        } => {
+            let axis = &r.column(2) * *half_height;
+            (vec![x + &axis, x - &axis], *radius)
+        }
+        Shape::Cylinder {
+            radius,
+            half_height,
+        } => {
+            let axis = r.column(2);
+            let mut rims = rim_points(&(x + &(&axis * *half_height)), r, *radius, normal);
+            rims.extend(rim_points(
+                &(x - &(&axis * *half_height)),
+                r,
+                *radius,
+                normal,
+            ));
+            (rims, 0.0)
+        }
+        Shape::Cone { radius, height } => {
+            let axis = r.column(2);
+            let base = x - &(&axis * (0.25 * height));
+            let mut points = rim_points(&base, r, *radius, normal);
+            points.push(x + &(&axis * (0.75 * height)));
+            (points, 0.0)
+        }
+        Shape::ConvexHull { vertices, .. } | Shape::TriMesh { vertices, .. } => {
+            (vertices.iter().map(|v| x + &(r * v)).collect(), 0.0)
+        }
+        // all parts share the plane normal, so their points form one manifold
+        Shape::Compound { .. } => {
+            let points: Vec<ContactPoint> = posed_parts(x, r, shape)
+                .into_iter()
+                .filter_map(|(x, r, part)| plane_shape_posed(normal, offset, &x, &r, part))
+                .flat_map(|manifold| manifold.points)
+                .collect();
+            return (!points.is_empty()).then(|| ContactManifold {
+                normal: normal.clone(),
+                points,
+            });
+        }
+    };
+    let points: Vec<ContactPoint> = candidates
+        .into_iter()
+        .filter_map(|candidate| {
+            let depth = offset - normal.dot(&candidate) + radius;
+            if depth > 0.0 {
+                Some(ContactPoint {
+                    point: candidate - &(normal * radius),
+                    depth,
+                })
+            } else {
+                None
+            }
+        })
+        .collect();
+    if points.is_empty() {
+        None
+    } else {
+        Some(ContactManifold {
+            normal: normal.clone(),
+            points,
+        })
+    }
+}
+
+// Four points on the circle around the z axis of `r` at `center`, the first one
+// furthest along -`normal`
+fn rim_points(center: &Vector, r: &Matrix, radius: f64, normal: &Vector) -> Vec<Vector> {
+    let axis = r.column(2);
+    let down = &(&axis * axis.dot(normal)) - normal;
+    let (t, u) = if down.magnitude() > 1e-6 {
+        let t = down.normalize();
+        let u = axis.cross(&t);
+        (t, u)
+    } else {
+        (r.column(0), r.column(1))
+    };
+    [&t * radius, &u * radius, &t * -radius, &u * -radius]
+        .iter()
+        .map(|d| center + d)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::approx_eq::{assert_approx_eq, ApproxEq};
+    use crate::math::quaternion::Quaternion;
+    use std::f64::consts::PI;
+
+    fn state(x: Vector, q: Quaternion) -> RigidBodyState {
+        RigidBodyState::new(
+            &x,
+            &q,
+            &Vector::zero(),
+            &Vector::zero(),
+            1.0,
+            &Matrix::identity(),
+        )
+    }
+
+    fn up() -> Vector {
+        Vector::new(0.0, 0.0, 1.0)
+    }
+
+    #[test]
+    fn test_plane_sphere() {
+        let s = state(
+            Vector::new(1.0, 2.0, 0.4),
+            Quaternion::coords(0.0, 0.0, 0.0, 1.0),
+        );
+        let manifold = plane_shape(&up(), 0.0, &s, &Shape::Sphere { radius: 0.5 }).unwrap();
+        assert_eq!(manifold.points.len(), 1);
+        assert_approx_eq!(manifold.points[0].depth, 0.1);
+        assert_approx_eq!(manifold.points[0].point, Vector::new(1.0, 2.0, -0.1));
+        assert!(plane_shape(&up(), -0.2, &s, &Shape::Sphere { radius: 0.5 }).is_none());
+    }
+
+    #[test]
+    fn test_plane_lying_capsule() {
+        let s = state(
+            Vector::new(0.0, 0.0, 0.45),
+            Quaternion::from_rotation(&Vector::new(1.0, 0.0, 0.0), PI / 2.0),
+        );
+        let capsule = Shape::Capsule {
+            radius: 0.5,
+            half_height: 1.0,
+        };
+        let manifold = plane_shape(&up(), 0.0, &s, &capsule).unwrap();
+        assert_eq!(manifold.points.len(), 2);
+        for c in &manifold.points {
+            assert_approx_eq!(c.depth, 0.05);
+            assert_approx_eq!(c.point.y.abs(), 1.0);
+        }
+    }
+
+    #[test]
+    fn test_plane_standing_cylinder() {
+        let s = state(
+            Vector::new(0.0, 0.0, 0.9),
+            Quaternion::coords(0.0, 0.0, 0.0, 1.0),
+        );
+        let cylinder = Shape::Cylinder {
+            radius: 0.5,
+            half_height: 1.0,
+        };
+        let manifold = plane_shape(&up(), 0.0, &s, &cylinder).unwrap();
+        assert_eq!(manifold.points.len(), 4);
+        for c in &manifold.points {
+            assert_approx_eq!(c.depth, 0.1);
+            assert_approx_eq!(c.point.x.hypot(c.point.y), 0.5);
+        }
+    }
+
+    #[test]
+    fn test_plane_tipped_cone() {
+        // lying on its side, touching along the line from the apex to the base rim
+        let (radius, height): (f64, f64) = (1.0, 2.0);
+        let slant = (radius / height).atan();
+        let q = Quaternion::from_rotation(&Vector::new(1.0, 0.0, 0.0), PI / 2.0 + slant);
+        let s = state(Vector::zero(), q);
+        let cone = Shape::Cone { radius, height };
+        let lowest = -0.75 * height * slant.sin();
+        let manifold = plane_shape(&up(), lowest + 0.01, &s, &cone).unwrap();
+        assert_eq!(manifold.points.len(), 2);
+        for c in &manifold.points {
+            assert_approx_eq!(c.depth, 0.01);
+        }
+    }
+}
@@ -0,0 +1,372 @@
+use super::cast::{time_of_impact, Point};
+use super::posed_parts;
+use crate::math::matrix::Matrix;
+use crate::math::sq;
+use crate::math::vector::Vector;
+use crate::world::shape::Shape;
+
+// Where the ray `origin + t direction` first enters a shape
+#[derive(Debug, Clone)]
+pub struct RayIntersection {
+    pub t: f64,
+    // outward normal of the surface at the hit
+    pub normal: Vector,
+}
+
+// Rays starting inside a solid hit it at once, facing back along the ray
+fn inside(direction: &Vector) -> Option<RayIntersection> {
+    Some(RayIntersection {
+        t: 0.0,
+        normal: -direction,
+    })
+}
+
+// Ray against the solid half space below the plane `normal . p = offset`, with
+// `direction` of unit length
+pub fn ray_plane(
+    origin: &Vector,
+    direction: &Vector,
+    max_t: f64,
+    normal: &Vector,
+    offset: f64,
+) -> Option<RayIntersection> {
+    let height = normal.dot(origin) - offset;
+    if height <= 0.0 {
+        return inside(direction);
+    }
+    let approach = -normal.dot(direction);
+    if approach <= 0.0 {
+        return None;
+    }
+    let t = height / approach;
+    (t <= max_t).then(|| RayIntersection {
+        t,
+        normal: normal.clone(),
+    })
+}
+
+// Ray against `shape` placed at `x` with orientation `r`, with `direction` of unit
+// length. Boxes, spheres and capsules are intersected exactly, other convex shapes
+// by conservative advancement. Meshes are hit as the convex hull of their vertices,
+// as they collide.
+pub fn ray_shape(
+    origin: &Vector,
+    direction: &Vector,
+    max_t: f64,
+    x: &Vector,
+    r: &Matrix,
+    shape: &Shape,
+) -> Option<RayIntersection> {
+    let rt = r.transpose();
+    let o = &rt * &(origin - x);
+    let d = &rt * direction;
+    let hit = match shape {
+        Shape::Box { half_extents } => ray_box(&o, &d, half_extents),
+        Shape::Sphere { radius } => ray_sphere(&o, &d, &Vector::zero(), *radius),
+        Shape::Capsule {
+            radius,
+            half_height,
+        } => ray_capsule(&o, &d, *radius, *half_height),
+        Shape::Compound { .. } => {
+            return posed_parts(x, r, shape)
+                .into_iter()
+                .filter_map(|(x, r, part)| ray_shape(origin, direction, max_t, &x, &r, part))
+                .min_by(|a, b| a.t.total_cmp(&b.t));
+        }
+        _ => ray_convex(&o, &d, max_t, shape),
+    }?;
+    (hit.t <= max_t).then(|| RayIntersection {
+        t: hit.t,
+        normal: r * &hit.normal,
+    })
+}
+
+// Slabs of a box centered on the origin
+pub(crate) fn ray_box(o: &Vector, d: &Vector, half_extents: &Vector) -> Option<RayIntersection> {
+    let slabs = [
+        (o.x, d.x, half_extents.x),
+        (o.y, d.y, half_extents.y),
+        (o.z, d.z, half_extents.z),
+    ];
+    let (mut enter, mut exit) = (f64::NEG_INFINITY, f64::INFINITY);
+    let mut normal = [0.0; 3];
+    for (axis, &(o, d, h)) in slabs.iter().enumerate() {
+        if d == 0.0 {
+            if o.abs() > h {
+                return None;
+            }
+            continue;
+        }
+        let (t1, t2) = ((-h - o) / d, (h - o) / d);
+        if t1.min(t2) > enter {
+            enter = t1.min(t2);
+            normal = [0.0; 3];
+            normal[axis] = -d.signum();
+        }
+        exit = exit.min(t1.max(t2));
+    }
+    if enter > exit || exit < 0.0 {
+        return None;
+    }
+    if enter <= 0.0 {
+        return inside(d);
+    }
+    Some(RayIntersection {
+        t: enter,
+        normal: Vector::new(normal[0], normal[1], normal[2]),
+    })
+}
+
+fn ray_sphere(o: &Vector, d: &Vector, center: &Vector, radius: f64) -> Option<RayIntersection> {
+    let m = o - center;
+    let b = m.dot(d);
+    let c = m.dot(&m) - sq(radius);
+    if c <= 0.0 {
+        return inside(d);
+    }
+    let discriminant = sq(b) - c;
+    if b > 0.0 || discriminant < 0.0 {
+        return None;
+    }
+    let t = -b - discriminant.sqrt();
+    Some(RayIntersection {
+        t,
+        normal: &(m + &(d * t)) / radius,
+    })
+}
+
+// Capsule along z: the side of its cylinder, then the spheres capping it
+fn ray_capsule(o: &Vector, d: &Vector, radius: f64, half_height: f64) -> Option<RayIntersection> {
+    let z = o.z.clamp(-half_height, half_height);
+    if sq(o.x) + sq(o.y) + sq(o.z - z) <= sq(radius) {
+        return inside(d);
+    }
+    let side = ray_side(o, d, radius, half_height);
+    let caps =
+        [-half_height, half_height].map(|z| ray_sphere(o, d, &Vector::new(0.0, 0.0, z), radius));
+    side.into_iter()
+        .chain(caps.into_iter().flatten())
+        .min_by(|a, b| a.t.total_cmp(&b.t))
+}
+
+// Side of the cylinder of `radius` around z between heights -half_height and
+// half_height, entered from outside
+fn ray_side(o: &Vector, d: &Vector, radius: f64, half_height: f64) -> Option<RayIntersection> {
+    let a = sq(d.x) + sq(d.y);
+    let b = o.x * d.x + o.y * d.y;
+    let c = sq(o.x) + sq(o.y) - sq(radius);
+    let discriminant = sq(b) - a * c;
+    if a == 0.0 || c < 0.0 || discriminant < 0.0 {
+        return None;
+    }
+    let t = (-b - discriminant.sqrt()) / a;
+    let p = o + &(d * t);
+    if t < 0.0 || p.z.abs() > half_height {
+        return None;
+    }
+    Some(RayIntersection {
+        t,
+        normal: Vector::new(p.x / radius, p.y / radius, 0.0),
+    })
+}
+
+fn ray_convex(o: &Vector, d: &Vector, max_t: f64, shape: &Shape) -> Option<RayIntersection> {
+    let impact = time_of_impact(&Point(o), d, max_t, shape)?;
+    Some(RayIntersection {
+        t: impact.t,
+        normal: impact.normal,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::approx_eq::{assert_approx_eq, ApproxEq};
+    use crate::math::quaternion::Quaternion;
+    use std::f64::consts::FRAC_PI_4;
+
+    fn cast(origin: Vector, direction: Vector, shape: &Shape) -> Option<RayIntersection> {
+        ray_shape(
+            &origin,
+            &direction.normalize(),
+            100.0,
+            &Vector::new(0.0, 0.0, 1.0),
+            &Matrix::identity(),
+            shape,
+        )
+    }
+
+    #[test]
+    fn test_ray_plane() {
+        let up = Vector::new(0.0, 0.0, 1.0);
+        let down = Vector::new(0.0, 0.0, -1.0);
+        let hit = ray_plane(&Vector::new(1.0, 2.0, 3.0), &down, 10.0, &up, 1.0).unwrap();
+        assert_approx_eq!(hit.t, 2.0);
+        assert_approx_eq!(hit.normal, up);
+        assert!(ray_plane(&Vector::new(1.0, 2.0, 3.0), &up, 10.0, &up, 1.0).is_none());
+        assert!(ray_plane(&Vector::new(1.0, 2.0, 3.0), &down, 1.5, &up, 1.0).is_none());
+        let below = ray_plane(&Vector::zero(), &up, 10.0, &up, 1.0).unwrap();
+        assert_approx_eq!(below.t, 0.0);
+    }
+
+    #[test]
+    fn test_ray_rotated_box() {
+        // a unit cube turned 45 degrees about z shows an edge to the ray
+        let r =
+            Quaternion::from_rotation(&Vector::new(0.0, 0.0, 1.0), FRAC_PI_4).to_rotation_matrix();
+        let shape = Shape::Box {
+            half_extents: Vector::new(0.5, 0.5, 0.5),
+        };
+        let direction = Vector::new(1.0, 0.0, 0.0);
+        let hit = ray_shape(
+            &Vector::new(-3.0, 0.1, 0.0),
+            &direction,
+            10.0,
+            &Vector::zero(),
+            &r,
+            &shape,
+        )
+        .unwrap();
+        assert_approx_eq!(hit.t, 3.0 - 0.5f64.sqrt() + 0.1);
+        assert_approx_eq!(hit.normal, Vector::new(-1.0, 1.0, 0.0).normalize());
+        let inside = ray_shape(
+            &Vector::zero(),
+            &direction,
+            10.0,
+            &Vector::zero(),
+            &r,
+            &shape,
+        );
+        assert_approx_eq!(inside.unwrap().t, 0.0);
+        let missed = Vector::new(-3.0, 0.8, 0.0);
+        assert!(ray_shape(&missed, &direction, 10.0, &Vector::zero(), &r, &shape).is_none());
+    }
+
+    #[test]
+    fn test_ray_sphere_and_capsule() {
+        let sphere = Shape::Sphere { radius: 1.0 };
+        let hit = cast(
+            Vector::new(0.0, -5.0, 1.0),
+            Vector::new(0.0, 1.0, 0.0),
+            &sphere,
+        );
+        assert_approx_eq!(hit.unwrap().t, 4.0);
+        let capsule = Shape::Capsule {
+            radius: 0.5,
+            half_height: 1.0,
+        };
+        // into the side of the cylinder
+        let side = cast(
+            Vector::new(-5.0, 0.0, 1.5),
+            Vector::new(1.0, 0.0, 0.0),
+            &capsule,
+        );
+        let side = side.unwrap();
+        assert_approx_eq!(side.t, 4.5);
+        assert_approx_eq!(side.normal, Vector::new(-1.0, 0.0, 0.0));
+        // down onto the top cap
+        let cap = cast(
+            Vector::new(0.0, 0.0, 5.0),
+            Vector::new(0.0, 0.0, -1.0),
+            &capsule,
+        );
+        assert_approx_eq!(cap.unwrap().t, 2.5);
+        let missed = cast(
+            Vector::new(-5.0, 0.0, 2.6),
+            Vector::new(1.0, 0.0, 0.0),
+            &capsule,
+        );
+        assert!(missed.is_none());
+    }
+
+    #[test]
+    fn test_ray_convex_shapes() {
+        let cylinder = Shape::Cylinder {
+            radius: 1.0,
+            half_height: 1.0,
+        };
+        let hit = cast(
+            Vector::new(-5.0, 0.0, 1.5),
+            Vector::new(1.0, 0.0, 0.0),
+            &cylinder,
+        );
+        let hit = hit.unwrap();
+        assert!((hit.t - 4.0).abs() < 1e-6, "t = {}", hit.t);
+        assert!(
+            (hit.normal.x + 1.0).abs() < 1e-6,
+            "normal = {:?}",
+            hit.normal
+        );
+        let cone = Shape::Cone {
+            radius: 1.0,
+            height: 4.0,
+        };
+        // the apex sits 3 above the center
+        let apex = cast(
+            Vector::new(0.0, 0.0, 10.0),
+            Vector::new(0.0, 0.0, -1.0),
+            &cone,
+        );
+        assert!((apex.unwrap().t - 6.0).abs() < 1e-6);
+        let missed = cast(
+            Vector::new(-5.0, 0.0, 5.0),
+            Vector::new(1.0, 0.0, 0.0),
+            &cone,
+        );
+        assert!(missed.is_none());
+    }
+
+    #[test]
+    fn test_ray_mesh_hits_its_hull() {
+        // a square pyramid with its apex 1 above the base, its base hollowed out
+        // up to a point 0.5 above the base center
+        let mesh = Shape::TriMesh {
+            vertices: vec![
+                Vector::new(-1.0, -1.0, 0.0),
+                Vector::new(1.0, -1.0, 0.0),
+                Vector::new(1.0, 1.0, 0.0),
+                Vector::new(-1.0, 1.0, 0.0),
+                Vector::new(0.0, 0.0, 1.0),
+                Vector::new(0.0, 0.0, 0.5),
+            ],
+            triangles: vec![
+                [0, 1, 4],
+                [1, 2, 4],
+                [2, 3, 4],
+                [3, 0, 4],
+                [1, 0, 5],
+                [2, 1, 5],
+                [3, 2, 5],
+                [0, 3, 5],
+            ],
+        };
+        let down = cast(
+            Vector::new(0.5, 0.0, 4.0),
+            Vector::new(0.0, 0.0, -1.0),
+            &mesh,
+        )
+        .unwrap();
+        assert!((down.t - 2.5).abs() < 1e-6, "t = {}", down.t);
+        assert!((&down.normal - &Vector::new(1.0, 0.0, 1.0).normalize()).magnitude() < 1e-6);
+        // into the hollow, which the hull fills
+        let up = cast(
+            Vector::new(0.0, 0.0, -2.0),
+            Vector::new(0.0, 0.0, 1.0),
+            &mesh,
+        )
+        .unwrap();
+        assert!((up.t - 3.0).abs() < 1e-6, "t = {}", up.t);
+        let inside = cast(
+            Vector::new(0.0, 0.0, 1.2),
+            Vector::new(1.0, 0.0, 0.0),
+            &mesh,
+        );
+        assert_approx_eq!(inside.unwrap().t, 0.0);
+        let missed = cast(
+            Vector::new(1.5, 0.0, 4.0),
+            Vector::new(0.0, 0.0, -1.0),
+            &mesh,
+        );
+        assert!(missed.is_none());
+    }
+}
@@ -0,0 +1,171 @@
+use crate::math::matrix::Matrix;
+use crate::math::vector::Vector;
+use crate::world::shape::Shape;
+
+// A convex set described by its furthest point in any direction
+pub trait SupportMap {
+    // `direction` need not be of unit length
+    fn support(&self, direction: &Vector) -> Vector;
+}
+
+// Meshes are treated as the convex hull of their vertices, compounds as the
+// convex hull of their children
+impl SupportMap for Shape {
+    fn support(&self, direction: &Vector) -> Vector {
+        let margin = self.margin();
+        let core = self.core_support(direction);
+        if margin == 0.0 {
+            return core;
+        }
+        let length = direction.magnitude();
+        if length == 0.0 {
+            core
+        } else {
+            core + &(direction * (margin / length))
+        }
+    }
+}
+
+impl Shape {
+    // Rounded shapes are a core point or segment swept by a sphere of this radius
+    pub fn margin(&self) -> f64 {
+        match self {
+            Shape::Sphere { radius } | Shape::Capsule { radius, .. } => *radius,
+            _ => 0.0,
+        }
+    }
+    // Support point of the shape without its margin
+    pub(crate) fn core_support(&self, d: &Vector) -> Vector {
+        let sign = |x: f64| if x < 0.0 { -1.0 } else { 1.0 };
+        // point on the circle of `radius` around z furthest along `d`
+        let rim = |radius: f64| {
+            let length = d.x.hypot(d.y);
+            if length == 0.0 {
+                Vector::zero()
+            } else {
+                Vector::new(d.x * radius / length, d.y * radius / length, 0.0)
+            }
+        };
+        match self {
+            Shape::Box { half_extents: h } => {
+                Vector::new(sign(d.x) * h.x, sign(d.y) * h.y, sign(d.z) * h.z)
+            }
+            Shape::Sphere { .. } => Vector::zero(),
+            Shape::Capsule { half_height, .. } => Vector::new(0.0, 0.0, sign(d.z) * half_height),
+            Shape::Cylinder {
+                radius,
+                half_height,
+            } => rim(*radius) + &Vector::new(0.0, 0.0, sign(d.z) * half_height),
+            Shape::Cone { radius, height } => {
+                let apex = Vector::new(0.0, 0.0, 0.75 * height);
+                let base = rim(*radius) + &Vector::new(0.0, 0.0, -0.25 * height);
+                if apex.dot(d) >= base.dot(d) {
+                    apex
+                } else {
+                    base
+                }
+            }
+            Shape::ConvexHull { vertices, .. } | Shape::TriMesh { vertices, .. } => vertices
+                .iter()
+                .max_by(|a, b| a.dot(d).total_cmp(&b.dot(d)))
+                .cloned()
+                .unwrap_or_else(Vector::zero),
+            Shape::Compound { children } => children
+                .iter()
+                .map(|child| {
+                    let r = child.orientation.to_rotation_matrix();
+                    &child.position + &(&r * &child.shape.support(&(&r.transpose() * d)))
+                })
+                .max_by(|a, b| a.dot(d).total_cmp(&b.dot(d)))
+                .unwrap_or_else(Vector::zero),
+        }
+    }
+}
+
+// A shape placed in the world at `x` with orientation `r`
+pub struct Posed<'a> {
+    pub x: &'a Vector,
+    pub r: &'a Matrix,
+    pub shape: &'a Shape,
+}
+
+impl SupportMap for Posed<'_> {
+    fn support(&self, direction: &Vector) -> Vector {
+        self.x + &(self.r * &self.shape.support(&(&self.r.transpose() * direction)))
+    }
+}
+
+// The core of a posed shape, without its margin
+pub(crate) struct PosedCore<'a>(pub(crate) Posed<'a>);
+
+impl SupportMap for PosedCore<'_> {
+    fn support(&self, direction: &Vector) -> Vector {
+        let Posed { x, r, shape } = &self.0;
+        *x + &(*r * &shape.core_support(&(&r.transpose() * direction)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::approx_eq::{assert_approx_eq, ApproxEq};
+    use crate::math::quaternion::Quaternion;
+    use std::f64::consts::FRAC_PI_2;
+
+    #[test]
+    fn test_box_and_sphere_support() {
+        let cuboid = Shape::Box {
+            half_extents: Vector::new(1.0, 2.0, 3.0),
+        };
+        assert_approx_eq!(
+            cuboid.support(&Vector::new(1.0, -1.0, 0.5)),
+            Vector::new(1.0, -2.0, 3.0)
+        );
+        let sphere = Shape::Sphere { radius: 2.0 };
+        assert_approx_eq!(
+            sphere.support(&Vector::new(0.0, 3.0, 0.0)),
+            Vector::new(0.0, 2.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn test_capsule_and_cone_support() {
+        let capsule = Shape::Capsule {
+            radius: 0.5,
+            half_height: 1.0,
+        };
+        assert_approx_eq!(
+            capsule.support(&Vector::new(0.0, 0.0, -1.0)),
+            Vector::new(0.0, 0.0, -1.5)
+        );
+        let cone = Shape::Cone {
+            radius: 1.0,
+            height: 4.0,
+        };
+        assert_approx_eq!(
+            cone.support(&Vector::new(0.0, 0.0, 1.0)),
+            Vector::new(0.0, 0.0, 3.0)
+        );
+        assert_approx_eq!(
+            cone.support(&Vector::new(1.0, 0.0, -0.1)),
+            Vector::new(1.0, 0.0, -1.0)
+        );
+    }
+
+    #[test]
+    fn test_posed_support() {
+        let r =
+            Quaternion::from_rotation(&Vector::new(0.0, 0.0, 1.0), FRAC_PI_2).to_rotation_matrix();
+        let x = Vector::new(5.0, 0.0, 0.0);
+        let shape = Shape::Box {
+            half_extents: Vector::new(2.0, 1.0, 1.0),
+        };
+        let posed = Posed {
+            x: &x,
+            r: &r,
+            shape: &shape,
+        };
+        // the long axis now points along y
+        assert_approx_eq!(posed.support(&Vector::new(0.0, 1.0, 0.0)).y, 2.0);
+    }
+}
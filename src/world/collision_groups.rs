@@ -0,0 +1,48 @@
+// Groups a collider belongs to and groups it may touch, one bit per group. Two
+// colliders interact only if each is a member of a group the other accepts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CollisionGroups {
+    pub memberships: u32,
+    pub filter: u32,
+}
+
+impl CollisionGroups {
+    pub const ALL: Self = Self::new(u32::MAX, u32::MAX);
+
+    pub const fn new(memberships: u32, filter: u32) -> Self {
+        Self {
+            memberships,
+            filter,
+        }
+    }
+    pub fn interacts_with(&self, other: &Self) -> bool {
+        self.memberships & other.filter != 0 && other.memberships & self.filter != 0
+    }
+}
+
+impl Default for CollisionGroups {
+    fn default() -> Self {
+        Self::ALL
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_groups_interact_both_ways() {
+        const PLAYER: u32 = 1;
+        const PROJECTILE: u32 = 2;
+        const WALL: u32 = 4;
+        let player = CollisionGroups::new(PLAYER, !PROJECTILE);
+        let projectile = CollisionGroups::new(PROJECTILE, u32::MAX);
+        let wall = CollisionGroups::new(WALL, u32::MAX);
+        assert!(!player.interacts_with(&projectile));
+        assert!(!projectile.interacts_with(&player));
+        assert!(projectile.interacts_with(&wall));
+        assert!(player.interacts_with(&wall));
+        assert!(CollisionGroups::default().interacts_with(&wall));
+        assert!(!CollisionGroups::new(0, u32::MAX).interacts_with(&wall));
+    }
+}
@@ -0,0 +1,187 @@
+use super::material::Material;
+use super::rigid_body::RigidBody;
+use super::shape::{Shape, ShapeChild};
+use crate::math::matrix::Matrix;
+use crate::math::quaternion::Quaternion;
+use crate::math::vector::Vector;
+
+// Several bodies rigidly attached to each other, moved so the combined center of
+// mass is at the origin
+pub struct CompoundBody {
+    children: Vec<ShapeChild>,
+    centroid: Vector,
+    mass: f64,
+    inertia: Matrix,
+    material: Option<Material>,
+}
+
+impl CompoundBody {
+    // Each child is posed by a position and orientation in the compound's
+    // coordinates. Children of one material give it to the compound. Panics
+    // without children.
+    pub fn new(children: &[(&dyn RigidBody, Vector, Quaternion)]) -> Self {
+        assert!(!children.is_empty(), "a compound body needs children");
+        let mass: f64 = children.iter().map(|(body, _, _)| body.mass()).sum();
+        // centers of mass of the children in compound coordinates
+        let centers: Vec<Vector> = children
+            .iter()
+            .map(|(body, position, orientation)| {
+                position + &(&orientation.to_rotation_matrix() * &body.center_of_mass())
+            })
+            .collect();
+        let mut moment = Vector::zero();
+        for ((body, _, _), center) in children.iter().zip(&centers) {
+            moment = moment + &(center * body.mass());
+        }
+        let centroid = &moment / mass;
+        let mut inertia = Matrix::new([0.0; 9]);
+        for ((body, _, orientation), center) in children.iter().zip(&centers) {
+            let r = orientation.to_rotation_matrix();
+            let rotated = &(&r * &body.inertia_tensor()) * &r.transpose();
+            // parallel axis theorem
+            let d = center - &centroid;
+            let shift = &(&Matrix::diagonal(1.0, 1.0, 1.0) * d.dot(&d)) - &Matrix::outer(&d, &d);
+            inertia = inertia + &rotated + &(&shift * body.mass());
+        }
+        Self {
+            children: children
+                .iter()
+                .map(|(body, position, orientation)| ShapeChild {
+                    position: position - &centroid,
+                    orientation: orientation.clone(),
+                    shape: body.shape(),
+                })
+                .collect(),
+            centroid,
+            mass,
+            inertia,
+            material: children
+                .iter()
+                .map(|(body, _, _)| body.material())
+                .reduce(|a, b| if a == b { a } else { None })
+                .flatten(),
+        }
+    }
+    // Surface material for the whole compound; the masses are still those of the
+    // children, which are made with their own densities
+    pub fn with_material(
+        children: &[(&dyn RigidBody, Vector, Quaternion)],
+        material: Material,
+    ) -> Self {
+        Self {
+            material: Some(material),
+            ..Self::new(children)
+        }
+    }
+    // Center of mass in the coordinates the children were given in
+    pub fn centroid(&self) -> &Vector {
+        &self.centroid
+    }
+    // Children posed relative to the center of mass
+    pub fn children(&self) -> &[ShapeChild] {
+        &self.children
+    }
+}
+
+impl RigidBody for CompoundBody {
+    fn mass(&self) -> f64 {
+        self.mass
+    }
+    fn inertia_tensor(&self) -> Matrix {
+        self.inertia.clone()
+    }
+    fn shape(&self) -> Shape {
+        Shape::Compound {
+            children: self.children.clone(),
+        }
+    }
+    fn material(&self) -> Option<Material> {
+        self.material
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::approx_eq::{assert_approx_eq, ApproxEq};
+    use crate::world::rigid_box::RigidBox;
+    use std::f64::consts::FRAC_PI_2;
+
+    fn identity() -> Quaternion {
+        Quaternion::from_rotation(&Vector::new(0.0, 0.0, 1.0), 0.0)
+    }
+
+    #[test]
+    #[should_panic(expected = "a compound body needs children")]
+    fn test_empty_compound_is_rejected() {
+        CompoundBody::new(&[]);
+    }
+
+    #[test]
+    fn test_two_halves_make_a_box() {
+        let half = RigidBox::new(1.0, 1.0, 1.0, 2.0);
+        let compound = CompoundBody::new(&[
+            (&half, Vector::new(1.5, 0.0, 0.0), identity()),
+            (&half, Vector::new(2.5, 0.0, 0.0), identity()),
+        ]);
+        let whole = RigidBox::new(2.0, 1.0, 1.0, 2.0);
+        assert_approx_eq!(compound.mass(), whole.mass());
+        assert_approx_eq!(compound.inertia_tensor(), whole.inertia_tensor());
+        assert_approx_eq!(compound.centroid(), &Vector::new(2.0, 0.0, 0.0));
+        assert_approx_eq!(compound.children()[0].position, Vector::new(-0.5, 0.0, 0.0));
+    }
+
+    #[test]
+    fn test_material_of_children() {
+        let steel = Material::new(0.6, 0.1, 7.8);
+        let rubber = Material::new(1.0, 0.8, 1.1);
+        let a = RigidBox::with_material(1.0, 1.0, 1.0, steel);
+        let b = RigidBox::with_material(1.0, 1.0, 1.0, steel);
+        let c = RigidBox::with_material(1.0, 1.0, 1.0, rubber);
+        let at = |x: f64| Vector::new(x, 0.0, 0.0);
+        let same = CompoundBody::new(&[(&a, at(0.0), identity()), (&b, at(1.0), identity())]);
+        assert_eq!(same.material(), Some(steel));
+        assert_approx_eq!(same.mass(), 15.6);
+        let mixed = [
+            (&a as &dyn RigidBody, at(0.0), identity()),
+            (&c, at(1.0), identity()),
+        ];
+        assert_eq!(CompoundBody::new(&mixed).material(), None);
+        let coated = CompoundBody::with_material(&mixed, rubber);
+        assert_eq!(coated.material(), Some(rubber));
+        assert_approx_eq!(coated.mass(), 8.9);
+    }
+
+    #[test]
+    fn test_rotated_child() {
+        // a 2 x 1 x 1 box turned a quarter around z is a 1 x 2 x 1 box
+        let bar = RigidBox::new(2.0, 1.0, 1.0, 1.0);
+        let turn = Quaternion::from_rotation(&Vector::new(0.0, 0.0, 1.0), FRAC_PI_2);
+        let compound = CompoundBody::new(&[(&bar, Vector::new(0.0, 0.0, 3.0), turn)]);
+        assert_approx_eq!(
+            compound.inertia_tensor(),
+            RigidBox::new(1.0, 2.0, 1.0, 1.0).inertia_tensor()
+        );
+    }
+
+    #[test]
+    fn test_table() {
+        // a top with four legs under its corners has products of inertia only
+        // when it is lopsided
+        let top = RigidBox::new(2.0, 2.0, 0.2, 1.0);
+        let leg = RigidBox::new(0.1, 0.1, 1.0, 1.0);
+        let mut parts: Vec<(&dyn RigidBody, Vector, Quaternion)> =
+            vec![(&top, Vector::new(0.0, 0.0, 1.1), identity())];
+        for (x, y) in [(-0.9, -0.9), (0.9, -0.9), (-0.9, 0.9), (0.9, 0.9)] {
+            parts.push((&leg, Vector::new(x, y, 0.5), identity()));
+        }
+        let table = CompoundBody::new(&parts);
+        let inertia = table.inertia_tensor();
+        assert_approx_eq!(table.mass(), 0.8 + 4.0 * 0.01);
+        assert_approx_eq!(inertia.column(0).y, 0.0);
+        assert_approx_eq!(inertia.column(0).z, 0.0);
+        parts.pop();
+        let lopsided = CompoundBody::new(&parts).inertia_tensor();
+        assert!(lopsided.column(0).y.abs() > 1e-4);
+    }
+}
@@ -0,0 +1,25 @@
+use super::query::Collider;
+use crate::math::vector::Vector;
+
+// What the solver did for a touching pair of colliders during one step
+#[derive(Debug, Clone)]
+pub struct ContactReport {
+    pub a: Collider,
+    pub b: Collider,
+    // total impulse applied to `b` over all contact points, `a` getting the opposite
+    pub impulse: Vector,
+    // the contact point which took the largest normal impulse, and its normal from
+    // `a` towards `b`
+    pub point: Vector,
+    pub normal: Vector,
+}
+
+// Changes in which colliders touch, static colliders always being `a`. Pairs whose
+// bodies are all asleep stay touching without further events.
+#[derive(Debug, Clone)]
+pub enum ContactEvent {
+    Started(ContactReport),
+    Persisted(ContactReport),
+    // also reported when one of the bodies is removed
+    Ended { a: Collider, b: Collider },
+}
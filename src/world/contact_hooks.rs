@@ -0,0 +1,14 @@
+use super::collision::ContactManifold;
+use super::query::Collider;
+
+// User callbacks run by `World::step` on pairs whose collision groups interact.
+// Static colliders are always `a`.
+pub trait ContactHooks {
+    // Whether the pair may touch at all, asked before the narrow phase
+    fn filter_pair(&self, _a: Collider, _b: Collider) -> bool {
+        true
+    }
+    // Lets contacts be changed before solving, e.g. for one way platforms; a
+    // manifold left without points is dropped
+    fn modify_contact(&self, _a: Collider, _b: Collider, _manifold: &mut ContactManifold) {}
+}
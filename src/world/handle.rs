@@ -0,0 +1,112 @@
+use std::marker::PhantomData;
+
+// Identifies a body in a `World`; stays invalid once the body is removed,
+// even if its slot is reused
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BodyHandle {
+    index: u32,
+    generation: u32,
+}
+
+// Generational handles a `HandleMap` hands out
+pub(crate) trait Handle: Copy {
+    fn new(index: u32, generation: u32) -> Self;
+    fn index(self) -> u32;
+    fn generation(self) -> u32;
+}
+
+impl Handle for BodyHandle {
+    fn new(index: u32, generation: u32) -> Self {
+        Self { index, generation }
+    }
+    fn index(self) -> u32 {
+        self.index
+    }
+    fn generation(self) -> u32 {
+        self.generation
+    }
+}
+
+struct Slot {
+    generation: u32,
+    // index into the dense object list, `None` when the slot is free
+    object: Option<usize>,
+}
+
+// Maps handles to indices of a densely packed list
+pub(crate) struct HandleMap<H> {
+    slots: Vec<Slot>,
+    free: Vec<u32>,
+    handles: PhantomData<H>,
+}
+
+impl<H: Handle> HandleMap<H> {
+    pub(crate) fn new() -> Self {
+        Self {
+            slots: Vec::new(),
+            free: Vec::new(),
+            handles: PhantomData,
+        }
+    }
+    pub(crate) fn insert(&mut self, object: usize) -> H {
+        match self.free.pop() {
+            Some(index) => {
+                let slot = &mut self.slots[index as usize];
+                slot.object = Some(object);
+                H::new(index, slot.generation)
+            }
+            None => {
+                self.slots.push(Slot {
+                    generation: 0,
+                    object: Some(object),
+                });
+                H::new(self.slots.len() as u32 - 1, 0)
+            }
+        }
+    }
+    pub(crate) fn get(&self, handle: H) -> Option<usize> {
+        self.slots
+            .get(handle.index() as usize)
+            .filter(|slot| slot.generation == handle.generation())
+            .and_then(|slot| slot.object)
+    }
+    // Points `handle` at a new index after the object list was rearranged
+    pub(crate) fn relocate(&mut self, handle: H, object: usize) {
+        self.slots[handle.index() as usize].object = Some(object);
+    }
+    pub(crate) fn remove(&mut self, handle: H) -> Option<usize> {
+        let object = self.get(handle)?;
+        let slot = &mut self.slots[handle.index() as usize];
+        slot.object = None;
+        slot.generation = slot.generation.wrapping_add(1);
+        self.free.push(handle.index());
+        Some(object)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_removed_handle_is_invalid() {
+        let mut map: HandleMap<BodyHandle> = HandleMap::new();
+        let a = map.insert(0);
+        let b = map.insert(1);
+        assert_eq!(map.remove(a), Some(0));
+        assert_eq!(map.get(a), None);
+        assert_eq!(map.get(b), Some(1));
+        assert_eq!(map.remove(a), None);
+    }
+
+    #[test]
+    fn test_reused_slot_gets_new_generation() {
+        let mut map: HandleMap<BodyHandle> = HandleMap::new();
+        let a = map.insert(0);
+        map.remove(a);
+        let b = map.insert(0);
+        assert_ne!(a, b);
+        assert_eq!(map.get(a), None);
+        assert_eq!(map.get(b), Some(0));
+    }
+}
@@ -0,0 +1,116 @@
+use super::{Derivative, Integrator};
+use crate::math::matrix::Matrix;
+use crate::math::vector::Vector;
+use crate::world::rigid_body_state::RigidBodyState;
+
+// Butcher tableau of the Dormand-Prince 5(4) pair
+const A: [&[f64]; 6] = [
+    &[1.0 / 5.0],
+    &[3.0 / 40.0, 9.0 / 40.0],
+    &[44.0 / 45.0, -56.0 / 15.0, 32.0 / 9.0],
+    &[
+        19372.0 / 6561.0,
+        -25360.0 / 2187.0,
+        64448.0 / 6561.0,
+        -212.0 / 729.0,
+    ],
+    &[
+        9017.0 / 3168.0,
+        -355.0 / 33.0,
+        46732.0 / 5247.0,
+        49.0 / 176.0,
+        -5103.0 / 18656.0,
+    ],
+    &[
+        35.0 / 384.0,
+        0.0,
+        500.0 / 1113.0,
+        125.0 / 192.0,
+        -2187.0 / 6784.0,
+        11.0 / 84.0,
+    ],
+];
+// difference between the fifth and fourth order weights
+const E: [f64; 7] = [
+    71.0 / 57600.0,
+    0.0,
+    -71.0 / 16695.0,
+    71.0 / 1920.0,
+    -17253.0 / 339200.0,
+    22.0 / 525.0,
+    -1.0 / 40.0,
+];
+
+// Adaptive fifth order Runge-Kutta, substepping each step until the local
+// error in position and orientation is within `tolerance`
+pub struct DormandPrince {
+    pub tolerance: f64,
+    pub min_step: f64,
+}
+
+impl Default for DormandPrince {
+    fn default() -> Self {
+        Self {
+            tolerance: 1e-8,
+            min_step: 1e-6,
+        }
+    }
+}
+
+impl Integrator for DormandPrince {
+    fn integrate(
+        &self,
+        s0: &RigidBodyState,
+        inv_mass: f64,
+        inv_inertia: &Matrix,
+        force: &Vector,
+        torque: &Vector,
+        dt: f64,
+    ) -> RigidBodyState {
+        let mut state = s0.clone();
+        let mut t = 0.0;
+        let mut h = dt;
+        while t < dt {
+            h = h.min(dt - t);
+            let mut k = vec![Derivative::new(&state, force, torque)];
+            // the last stage is evaluated at the fifth order solution
+            let mut next = state.clone();
+            for a in A {
+                let terms: Vec<(f64, &Derivative)> = a.iter().cloned().zip(&k).collect();
+                next = Derivative::combine(&terms).advance(&state, h, inv_mass, inv_inertia);
+                k.push(Derivative::new(&next, force, torque));
+            }
+            let terms: Vec<(f64, &Derivative)> = E.iter().cloned().zip(&k).collect();
+            let e = Derivative::combine(&terms);
+            let error = h
+                * (e.dx
+                    .magnitude()
+                    .max((e.dq.v.dot(&e.dq.v) + e.dq.w * e.dq.w).sqrt()));
+            if error <= self.tolerance || h <= self.min_step {
+                state = next;
+                t += h;
+            }
+            let factor = if error > 0.0 {
+                0.9 * (self.tolerance / error).powf(0.2)
+            } else {
+                5.0
+            };
+            h = (h * factor.clamp(0.2, 5.0)).max(self.min_step);
+        }
+        state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::integrator::tests::{errors, run};
+
+    #[test]
+    fn test_dormand_prince_accuracy() {
+        // a single large step is subdivided to meet the tolerance
+        let (ex, eq) = errors(&run(&DormandPrince::default(), 1, 1.0), 1.0);
+        assert!(ex < 1e-8, "position error {}", ex);
+        assert!(eq < 1e-7, "orientation error {}", eq);
+    }
+}
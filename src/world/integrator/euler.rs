@@ -0,0 +1,42 @@
+use super::Integrator;
+use crate::math::matrix::Matrix;
+use crate::math::quaternion::Quaternion;
+use crate::math::vector::Vector;
+use crate::world::rigid_body_state::RigidBodyState;
+
+// First order symplectic Euler: momenta first, then positions from the new velocities
+pub struct SemiImplicitEuler;
+
+impl Integrator for SemiImplicitEuler {
+    fn integrate(
+        &self,
+        s0: &RigidBodyState,
+        inv_mass: f64,
+        inv_inertia: &Matrix,
+        force: &Vector,
+        torque: &Vector,
+        dt: f64,
+    ) -> RigidBodyState {
+        let p = &s0.p + &(force * dt);
+        let l = &s0.l + &(torque * dt);
+        let v = &p * inv_mass;
+        let w = &s0.inv_inertia_world(inv_inertia) * &l;
+        let x = &s0.x + &(&v * dt);
+        let q = &s0.q + &(&(&Quaternion::new(&w * 0.5, 0.0) * &s0.q) * dt);
+        RigidBodyState::new(&x, &q, &p, &l, inv_mass, inv_inertia)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::integrator::tests::{errors, run};
+
+    #[test]
+    fn test_semi_implicit_euler_accuracy() {
+        // first order, the position error is dt * t / 2 under constant force
+        let (ex, eq) = errors(&run(&SemiImplicitEuler, 100, 0.01), 1.0);
+        assert!((ex - 0.005).abs() < 1e-10, "position error {}", ex);
+        assert!(eq < 1e-2, "orientation error {}", eq);
+    }
+}
@@ -0,0 +1,110 @@
+use super::rigid_body_state::RigidBodyState;
+use crate::math::matrix::Matrix;
+use crate::math::quaternion::Quaternion;
+use crate::math::vector::Vector;
+
+pub mod dormand_prince;
+pub mod euler;
+pub mod rk4;
+pub mod verlet;
+
+// Advances a body state by `dt` under a force and torque held constant over the step
+pub trait Integrator {
+    fn integrate(
+        &self,
+        state: &RigidBodyState,
+        inv_mass: f64,
+        inv_inertia: &Matrix,
+        force: &Vector,
+        torque: &Vector,
+        dt: f64,
+    ) -> RigidBodyState;
+}
+
+// Time derivative of the state variables x, q, p and l
+pub(crate) struct Derivative {
+    dx: Vector,
+    dq: Quaternion,
+    dp: Vector,
+    dl: Vector,
+}
+
+impl Derivative {
+    pub(crate) fn new(state: &RigidBodyState, force: &Vector, torque: &Vector) -> Self {
+        Self {
+            dx: state.v.clone(),
+            dq: &Quaternion::new(&state.w * 0.5, 0.0) * &state.q,
+            dp: force.clone(),
+            dl: torque.clone(),
+        }
+    }
+    // Weighted sum of derivatives
+    pub(crate) fn combine(terms: &[(f64, &Derivative)]) -> Self {
+        terms.iter().fold(
+            Self {
+                dx: Vector::zero(),
+                dq: Quaternion::coords(0.0, 0.0, 0.0, 0.0),
+                dp: Vector::zero(),
+                dl: Vector::zero(),
+            },
+            |sum, (c, d)| Self {
+                dx: sum.dx + &(&d.dx * *c),
+                dq: sum.dq + &(&d.dq * *c),
+                dp: sum.dp + &(&d.dp * *c),
+                dl: sum.dl + &(&d.dl * *c),
+            },
+        )
+    }
+    // s + h * self
+    pub(crate) fn advance(
+        &self,
+        s: &RigidBodyState,
+        h: f64,
+        inv_mass: f64,
+        inv_inertia: &Matrix,
+    ) -> RigidBodyState {
+        let x = &s.x + &(&self.dx * h);
+        let q = &s.q + &(&self.dq * h);
+        let p = &s.p + &(&self.dp * h);
+        let l = &s.l + &(&self.dl * h);
+        RigidBodyState::new(&x, &q, &p, &l, inv_mass, inv_inertia)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::math::sq;
+
+    // Box with principal moments 1, 2 and 3, unit mass
+    pub(crate) fn inv_inertia() -> Matrix {
+        Matrix::new([1.0, 0.0, 0.0, 0.0, 0.5, 0.0, 0.0, 0.0, 1.0 / 3.0])
+    }
+
+    // Falls under unit gravity while spinning about its z axis
+    pub(crate) fn initial_state() -> RigidBodyState {
+        RigidBodyState::new(
+            &Vector::new(1.0, 2.0, 3.0),
+            &Quaternion::coords(0.0, 0.0, 0.0, 1.0),
+            &Vector::new(0.5, 0.0, 2.0),
+            &Vector::new(0.0, 0.0, 3.0),
+            1.0,
+            &inv_inertia(),
+        )
+    }
+
+    pub(crate) fn run<I: Integrator>(integrator: &I, steps: usize, dt: f64) -> RigidBodyState {
+        let force = Vector::new(0.0, 0.0, -1.0);
+        (0..steps).fold(initial_state(), |s, _| {
+            integrator.integrate(&s, 1.0, &inv_inertia(), &force, &Vector::zero(), dt)
+        })
+    }
+
+    // Position and orientation errors against the exact motion after time `t`
+    pub(crate) fn errors(s: &RigidBodyState, t: f64) -> (f64, f64) {
+        let x = Vector::new(1.0 + 0.5 * t, 2.0, 3.0 + 2.0 * t - 0.5 * sq(t));
+        let q = Quaternion::from_rotation(&Vector::new(0.0, 0.0, 1.0), t);
+        let dq = &s.q + &(&q * -1.0);
+        ((&s.x - &x).magnitude(), (dq.v.dot(&dq.v) + sq(dq.w)).sqrt())
+    }
+}
@@ -0,0 +1,101 @@
+use super::Integrator;
+use crate::math::matrix::Matrix;
+use crate::math::quaternion::Quaternion;
+use crate::math::vector::Vector;
+use crate::world::rigid_body_state::RigidBodyState;
+
+// Classic fourth order Runge-Kutta
+pub struct Rk4;
+
+impl Integrator for Rk4 {
+    fn integrate(
+        &self,
+        s0: &RigidBodyState,
+        inv_mass: f64,
+        inv_inertia: &Matrix,
+        force: &Vector,
+        torque: &Vector,
+        dt: f64,
+    ) -> RigidBodyState {
+        let halfdt = 0.5 * dt;
+        let thirddt = dt / 3.0;
+        let sixthdt = dt / 6.0;
+
+        // a1 = G(t, s0), b1 = s0 + (dt / 2) * a1
+        let a1dxdt = &s0.v;
+        let a1dqdt = &Quaternion::new(&s0.w * 0.5, 0.0) * &s0.q;
+        let a1dpdt = force;
+        let a1dldt = torque;
+
+        let x = &s0.x + &(a1dxdt * halfdt);
+        let q = &s0.q + &(&a1dqdt * halfdt);
+        let p = &s0.p + &(a1dpdt * halfdt);
+        let l = &s0.l + &(a1dldt * halfdt);
+        let b1 = RigidBodyState::new(&x, &q, &p, &l, inv_mass, inv_inertia);
+
+        // a2 = G(t + dt / 2, b1), b2 = s0 + (dt / 2) * a2
+        let a2dxdt = &b1.v;
+        let a2dqdt = &Quaternion::new(&b1.w * 0.5, 0.0) * &b1.q;
+        let a2dpdt = force;
+        let a2dldt = torque;
+
+        let x = &s0.x + &(a2dxdt * halfdt);
+        let q = &s0.q + &(&a2dqdt * halfdt);
+        let p = &s0.p + &(a2dpdt * halfdt);
+        let l = &s0.l + &(a2dldt * halfdt);
+        let b2 = RigidBodyState::new(&x, &q, &p, &l, inv_mass, inv_inertia);
+
+        // a3 = G(t + dt / 2, b2), b3 = s0 + dt * a3
+        let a3dxdt = &b2.v;
+        let a3dqdt = &Quaternion::new(&b2.w * 0.5, 0.0) * &b2.q;
+        let a3dpdt = force;
+        let a3dldt = torque;
+
+        let x = &s0.x + &(a3dxdt * dt);
+        let q = &s0.q + &(&a3dqdt * dt);
+        let p = &s0.p + &(a3dpdt * dt);
+        let l = &s0.l + &(a3dldt * dt);
+        let b3 = RigidBodyState::new(&x, &q, &p, &l, inv_mass, inv_inertia);
+
+        // a4 = G(t + dt, b4), s1 = s0 + (dt / 6) * (a1 + 2 * a2 + 2 * a3 + a4)
+        let a4dxdt = &b3.v;
+        let a4dqdt = &Quaternion::new(&b3.w * 0.5, 0.0) * &b3.q;
+        let a4dpdt = force;
+        let a4dldt = torque;
+
+        let x = &s0.x
+            + &(a1dxdt * sixthdt)
+            + &(a2dxdt * thirddt)
+            + &(a3dxdt * thirddt)
+            + &(a4dxdt * sixthdt);
+        let q = &s0.q
+            + &(&a1dqdt * sixthdt)
+            + &(&a2dqdt * thirddt)
+            + &(&a3dqdt * thirddt)
+            + &(&a4dqdt * sixthdt);
+        let p = &s0.p
+            + &(a1dpdt * sixthdt)
+            + &(a2dpdt * thirddt)
+            + &(a3dpdt * thirddt)
+            + &(a4dpdt * sixthdt);
+        let l = &s0.l
+            + &(a1dldt * sixthdt)
+            + &(a2dldt * thirddt)
+            + &(a3dldt * thirddt)
+            + &(a4dldt * sixthdt);
+        RigidBodyState::new(&x, &q, &p, &l, inv_mass, inv_inertia)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::integrator::tests::{errors, run};
+
+    #[test]
+    fn test_rk4_accuracy() {
+        let (ex, eq) = errors(&run(&Rk4, 100, 0.01), 1.0);
+        assert!(ex < 1e-10, "position error {}", ex);
+        assert!(eq < 1e-6, "orientation error {}", eq);
+    }
+}
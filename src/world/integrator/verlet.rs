@@ -0,0 +1,49 @@
+use super::Integrator;
+use crate::math::matrix::Matrix;
+use crate::math::quaternion::Quaternion;
+use crate::math::vector::Vector;
+use crate::world::rigid_body_state::RigidBodyState;
+
+// Second order symplectic velocity Verlet, as a half kick, a drift and a half kick
+pub struct VelocityVerlet;
+
+impl Integrator for VelocityVerlet {
+    fn integrate(
+        &self,
+        s0: &RigidBodyState,
+        inv_mass: f64,
+        inv_inertia: &Matrix,
+        force: &Vector,
+        torque: &Vector,
+        dt: f64,
+    ) -> RigidBodyState {
+        let halfdt = 0.5 * dt;
+        let p = &s0.p + &(force * halfdt);
+        let l = &s0.l + &(torque * halfdt);
+
+        // drift the orientation with the angular velocity at the midpoint
+        let w = &s0.inv_inertia_world(inv_inertia) * &l;
+        let qmid = &s0.q + &(&(&Quaternion::new(&w * 0.5, 0.0) * &s0.q) * halfdt);
+        let mid = RigidBodyState::new(&s0.x, &qmid, &p, &l, inv_mass, inv_inertia);
+        let x = &s0.x + &(&mid.v * dt);
+        let q = &s0.q + &(&(&Quaternion::new(&mid.w * 0.5, 0.0) * &mid.q) * dt);
+
+        let p = &p + &(force * halfdt);
+        let l = &l + &(torque * halfdt);
+        RigidBodyState::new(&x, &q, &p, &l, inv_mass, inv_inertia)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::integrator::tests::{errors, run};
+
+    #[test]
+    fn test_velocity_verlet_accuracy() {
+        // exact for constant forces
+        let (ex, eq) = errors(&run(&VelocityVerlet, 100, 0.01), 1.0);
+        assert!(ex < 1e-10, "position error {}", ex);
+        assert!(eq < 1e-4, "orientation error {}", eq);
+    }
+}
@@ -0,0 +1,82 @@
+#[derive(Debug, Clone)]
+pub struct SleepConfig {
+    pub enabled: bool,
+    // bodies slower than these may fall asleep
+    pub linear_threshold: f64,
+    pub angular_threshold: f64,
+    // how long every body of an island must stay slow before it sleeps
+    pub time_to_sleep: f64,
+}
+
+impl Default for SleepConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            linear_threshold: 0.1,
+            angular_threshold: 0.1,
+            time_to_sleep: 0.5,
+        }
+    }
+}
+
+// Disjoint sets of bodies, joined when they touch or are jointed
+pub(crate) struct UnionFind {
+    parent: Vec<usize>,
+    rank: Vec<u8>,
+}
+
+impl UnionFind {
+    pub(crate) fn new(count: usize) -> Self {
+        Self {
+            parent: (0..count).collect(),
+            rank: vec![0; count],
+        }
+    }
+    // Representative of the set holding `i`
+    pub(crate) fn find(&mut self, i: usize) -> usize {
+        let mut root = i;
+        while self.parent[root] != root {
+            root = self.parent[root];
+        }
+        // path compression
+        let mut i = i;
+        while self.parent[i] != root {
+            let next = self.parent[i];
+            self.parent[i] = root;
+            i = next;
+        }
+        root
+    }
+    pub(crate) fn union(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.find(a), self.find(b));
+        if a == b {
+            return;
+        }
+        match self.rank[a].cmp(&self.rank[b]) {
+            std::cmp::Ordering::Less => self.parent[a] = b,
+            std::cmp::Ordering::Greater => self.parent[b] = a,
+            std::cmp::Ordering::Equal => {
+                self.parent[b] = a;
+                self.rank[a] += 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_union_find() {
+        let mut sets = UnionFind::new(6);
+        sets.union(0, 1);
+        sets.union(2, 3);
+        sets.union(1, 3);
+        assert_eq!(sets.find(0), sets.find(2));
+        assert_ne!(sets.find(0), sets.find(4));
+        assert_ne!(sets.find(4), sets.find(5));
+        sets.union(5, 4);
+        assert_eq!(sets.find(4), sets.find(5));
+    }
+}
@@ -0,0 +1,593 @@
+use super::handle::{BodyHandle, Handle};
+use super::solver::{Row, SolverBody};
+use crate::math::matrix::Matrix;
+use crate::math::quaternion::Quaternion;
+use crate::math::vector::Vector;
+
+// Identifies a joint in a `World`; stays invalid once the joint is removed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct JointHandle {
+    index: u32,
+    generation: u32,
+}
+
+impl Handle for JointHandle {
+    fn new(index: u32, generation: u32) -> Self {
+        Self { index, generation }
+    }
+    fn index(self) -> u32 {
+        self.index
+    }
+    fn generation(self) -> u32 {
+        self.generation
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum JointKind {
+    // the anchors coincide
+    Ball,
+    // the anchors coincide and the bodies only turn about the axis
+    Hinge,
+    // the bodies only move along the axis, without turning
+    Slider,
+    // the bodies move as one
+    Fixed,
+    // the anchors stay `length` apart
+    Distance { length: f64 },
+}
+
+// Range of the angle of a hinge or the offset of a slider from where it was added
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct JointLimit {
+    pub lower: f64,
+    pub upper: f64,
+    // fraction of the speed kept when bouncing off a limit
+    pub restitution: f64,
+}
+
+// Drives a hinge or slider; forces are torques for hinges
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum JointMotor {
+    // towards a joint speed
+    Velocity {
+        speed: f64,
+        max_force: f64,
+    },
+    // spring and damper towards a joint position
+    Servo {
+        target: f64,
+        stiffness: f64,
+        damping: f64,
+        max_force: f64,
+    },
+}
+
+// A joint given in world coordinates, for the bodies as they are posed when it is
+// added to a `World`
+#[derive(Debug, Clone)]
+pub struct Joint {
+    pub(crate) kind: JointKind,
+    pub(crate) anchor_a: Vector,
+    pub(crate) anchor_b: Vector,
+    pub(crate) axis: Vector,
+    pub(crate) limit: Option<JointLimit>,
+    pub(crate) motor: Option<JointMotor>,
+}
+
+impl Joint {
+    fn new(kind: JointKind, anchor_a: &Vector, anchor_b: &Vector, axis: &Vector) -> Self {
+        Self {
+            kind,
+            anchor_a: anchor_a.clone(),
+            anchor_b: anchor_b.clone(),
+            axis: axis.normalize(),
+            limit: None,
+            motor: None,
+        }
+    }
+    pub fn ball(anchor: &Vector) -> Self {
+        Self::new(JointKind::Ball, anchor, anchor, &Vector::new(0.0, 0.0, 1.0))
+    }
+    pub fn hinge(anchor: &Vector, axis: &Vector) -> Self {
+        Self::new(JointKind::Hinge, anchor, anchor, axis)
+    }
+    pub fn slider(anchor: &Vector, axis: &Vector) -> Self {
+        Self::new(JointKind::Slider, anchor, anchor, axis)
+    }
+    pub fn fixed(anchor: &Vector) -> Self {
+        Self::new(
+            JointKind::Fixed,
+            anchor,
+            anchor,
+            &Vector::new(0.0, 0.0, 1.0),
+        )
+    }
+    // Keeps the anchors at their current distance
+    pub fn distance(anchor_a: &Vector, anchor_b: &Vector) -> Self {
+        let length = (anchor_b - anchor_a).magnitude();
+        Self::new(
+            JointKind::Distance { length },
+            anchor_a,
+            anchor_b,
+            &Vector::new(0.0, 0.0, 1.0),
+        )
+    }
+    pub fn kind(&self) -> JointKind {
+        self.kind
+    }
+    // Limits and motors only act on hinges and sliders
+    pub fn with_limit(mut self, limit: JointLimit) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+    pub fn with_motor(mut self, motor: JointMotor) -> Self {
+        self.motor = Some(motor);
+        self
+    }
+}
+
+// Pose of a jointed body
+#[derive(Clone, Copy)]
+pub(crate) struct Pose<'a> {
+    pub(crate) x: &'a Vector,
+    pub(crate) q: &'a Quaternion,
+    pub(crate) r: &'a Matrix,
+}
+
+static ORIGIN: Vector = Vector::new(0.0, 0.0, 0.0);
+static IDENTITY: Quaternion = Quaternion::coords(0.0, 0.0, 0.0, 1.0);
+static UNROTATED: Matrix = Matrix::identity();
+
+impl Pose<'static> {
+    // The frame of joints attached to the world
+    pub(crate) fn world() -> Self {
+        Self {
+            x: &ORIGIN,
+            q: &IDENTITY,
+            r: &UNROTATED,
+        }
+    }
+}
+
+// A joint between body `b` and body `a`, or the world when `a` is None, with
+// anchors and axes in the coordinates of each body
+pub(crate) struct AttachedJoint {
+    pub(crate) a: Option<BodyHandle>,
+    pub(crate) b: BodyHandle,
+    kind: JointKind,
+    anchor_a: Vector,
+    anchor_b: Vector,
+    axis_a: Vector,
+    axis_b: Vector,
+    // orientation of b relative to a when the joint was added
+    rest: Quaternion,
+    limit: Option<JointLimit>,
+    pub(crate) motor: Option<JointMotor>,
+}
+
+impl AttachedJoint {
+    pub(crate) fn new(
+        joint: &Joint,
+        a: Option<BodyHandle>,
+        pose_a: &Pose,
+        b: BodyHandle,
+        pose_b: &Pose,
+    ) -> Self {
+        let (ra, rb) = (pose_a.r.transpose(), pose_b.r.transpose());
+        Self {
+            a,
+            b,
+            kind: joint.kind,
+            anchor_a: &ra * &(&joint.anchor_a - pose_a.x),
+            anchor_b: &rb * &(&joint.anchor_b - pose_b.x),
+            axis_a: &ra * &joint.axis,
+            axis_b: &rb * &joint.axis,
+            rest: &pose_a.q.conj() * pose_b.q,
+            limit: joint.limit,
+            motor: joint.motor,
+        }
+    }
+    // Small rotation taking the rest orientation of b to its current one
+    fn drift(&self, pose_a: &Pose, pose_b: &Pose) -> Quaternion {
+        let q = pose_b.q * &(pose_a.q * &self.rest).conj();
+        if q.w < 0.0 {
+            &q * -1.0
+        } else {
+            q
+        }
+    }
+    // Angle of a hinge about its axis or offset of a slider along its axis, from
+    // where the joint was added
+    pub(crate) fn position(&self, pose_a: &Pose, pose_b: &Pose) -> Option<f64> {
+        let axis = pose_a.r * &self.axis_a;
+        match self.kind {
+            JointKind::Hinge => {
+                let q = self.drift(pose_a, pose_b);
+                Some(2.0 * q.v.dot(&axis).atan2(q.w))
+            }
+            JointKind::Slider => {
+                let pa = pose_a.x + &(pose_a.r * &self.anchor_a);
+                let pb = pose_b.x + &(pose_b.r * &self.anchor_b);
+                Some((&pb - &pa).dot(&axis))
+            }
+            _ => None,
+        }
+    }
+    // Velocity rows for solver bodies `ia` and `ib`, which correct a fraction
+    // `stiffness` of the position error per second
+    pub(crate) fn rows(
+        &self,
+        bodies: &[SolverBody],
+        (ia, pose_a): (usize, &Pose),
+        (ib, pose_b): (usize, &Pose),
+        correction_factor: f64,
+        dt: f64,
+    ) -> Vec<Row> {
+        let stiffness = correction_factor / dt;
+        let pa = pose_a.x + &(pose_a.r * &self.anchor_a);
+        let pb = pose_b.x + &(pose_b.r * &self.anchor_b);
+        let arm_a = &pa - pose_a.x;
+        let arm_b = &pb - pose_b.x;
+        let axis = pose_a.r * &self.axis_a;
+        let (t, u) = axis.orthonormal_basis();
+        let error = &pb - &pa;
+        let mut rows = Vec::new();
+        // keeps the anchors together along `direction`
+        let linear = |direction: &Vector, rows: &mut Vec<Row>| {
+            let mut row = Row::new(
+                bodies,
+                ia,
+                ib,
+                direction.clone(),
+                arm_a.cross(direction),
+                arm_b.cross(direction),
+            );
+            row.bias = -stiffness * error.dot(direction);
+            rows.push(row);
+        };
+        let units = [
+            Vector::new(1.0, 0.0, 0.0),
+            Vector::new(0.0, 1.0, 0.0),
+            Vector::new(0.0, 0.0, 1.0),
+        ];
+        let drift = &self.drift(pose_a, pose_b).v * 2.0;
+        // keeps the relative rotation zero about `direction`
+        let angular = |direction: &Vector, error: f64| {
+            let mut row = Row::new(
+                bodies,
+                ia,
+                ib,
+                Vector::zero(),
+                direction.clone(),
+                direction.clone(),
+            );
+            row.bias = -stiffness * error;
+            row
+        };
+        match self.kind {
+            JointKind::Ball => units.iter().for_each(|e| linear(e, &mut rows)),
+            JointKind::Fixed => {
+                units.iter().for_each(|e| linear(e, &mut rows));
+                for e in &units {
+                    rows.push(angular(e, drift.dot(e)));
+                }
+            }
+            JointKind::Hinge => {
+                units.iter().for_each(|e| linear(e, &mut rows));
+                let misalignment = axis.cross(&(pose_b.r * &self.axis_b));
+                for e in [&t, &u] {
+                    rows.push(angular(e, misalignment.dot(e)));
+                }
+            }
+            JointKind::Slider => {
+                for e in [&t, &u] {
+                    rows.push(Row::at_point(bodies, ia, ib, &pb, e));
+                    let last = rows.len() - 1;
+                    rows[last].bias = -stiffness * error.dot(e);
+                }
+                for e in &units {
+                    rows.push(angular(e, drift.dot(e)));
+                }
+            }
+            JointKind::Distance { length } => {
+                let distance = error.magnitude();
+                if distance > 0.0 {
+                    let n = &error / distance;
+                    let mut row =
+                        Row::new(bodies, ia, ib, n.clone(), arm_a.cross(&n), arm_b.cross(&n));
+                    row.bias = -stiffness * (distance - length);
+                    rows.push(row);
+                }
+            }
+        }
+        // the row along the free axis of a hinge or slider
+        let free_row = || match self.kind {
+            JointKind::Hinge => {
+                Row::new(bodies, ia, ib, Vector::zero(), axis.clone(), axis.clone())
+            }
+            _ => Row::at_point(bodies, ia, ib, &pb, &axis),
+        };
+        let Some(position) = self.position(pose_a, pose_b) else {
+            return rows;
+        };
+        if let Some(limit) = self.limit {
+            let mut row = free_row();
+            let speed = row.velocity(bodies, false);
+            if position <= limit.lower {
+                row.lower = 0.0;
+                row.bias = (-limit.restitution * speed).max(stiffness * (limit.lower - position));
+                rows.push(row);
+            } else if position >= limit.upper {
+                row.upper = 0.0;
+                row.bias = (-limit.restitution * speed).min(stiffness * (limit.upper - position));
+                rows.push(row);
+            }
+        }
+        match self.motor {
+            Some(JointMotor::Velocity { speed, max_force }) => {
+                let mut row = free_row();
+                row.bias = speed;
+                (row.lower, row.upper) = (-max_force * dt, max_force * dt);
+                rows.push(row);
+            }
+            Some(JointMotor::Servo {
+                target,
+                stiffness,
+                damping,
+                max_force,
+            }) if stiffness > 0.0 || damping > 0.0 => {
+                let mut row = free_row();
+                row.soften(stiffness, damping, position - target, dt);
+                (row.lower, row.upper) = (-max_force * dt, max_force * dt);
+                rows.push(row);
+            }
+            _ => (),
+        }
+        rows
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::rigid_box::RigidBox;
+    use crate::world::rigid_sphere::RigidSphere;
+    use crate::world::world::World;
+
+    fn identity() -> Quaternion {
+        Quaternion::coords(0.0, 0.0, 0.0, 1.0)
+    }
+
+    fn add_box(world: &mut World, x: &Vector, v: &Vector) -> BodyHandle {
+        world.add(
+            &RigidBox::new(1.0, 1.0, 1.0, 1.0),
+            x,
+            &identity(),
+            v,
+            &Vector::zero(),
+        )
+    }
+
+    fn run(world: &mut World, steps: usize) {
+        let mut t = 0.0;
+        for _ in 0..steps {
+            t = world.step(t, 0.01);
+        }
+    }
+
+    #[test]
+    fn test_pendulum_keeps_its_length() {
+        let mut world = World::new(Vector::new(0.0, 0.0, -10.0));
+        let bob = world.add(
+            &RigidSphere::new(0.1, 1.0),
+            &Vector::new(1.0, 0.0, 0.0),
+            &identity(),
+            &Vector::zero(),
+            &Vector::zero(),
+        );
+        world.add_joint(
+            None,
+            bob,
+            &Joint::distance(&Vector::zero(), &Vector::new(1.0, 0.0, 0.0)),
+        );
+        for _ in 0..20 {
+            run(&mut world, 10);
+            let length = world.get(bob).unwrap().position().magnitude();
+            assert!((length - 1.0).abs() < 0.02, "length = {}", length);
+        }
+        // swung down
+        assert!(world.get(bob).unwrap().position().z < -0.5);
+    }
+
+    #[test]
+    fn test_ball_joint_holds_anchor() {
+        let mut world = World::new(Vector::new(0.0, 0.0, -10.0));
+        let b = add_box(&mut world, &Vector::new(0.5, 0.5, 0.5), &Vector::zero());
+        let corner = Vector::zero();
+        world.add_joint(None, b, &Joint::ball(&corner));
+        run(&mut world, 200);
+        let object = world.get(b).unwrap();
+        let arm = &object.orientation().to_rotation_matrix() * &Vector::new(-0.5, -0.5, -0.5);
+        let anchor = object.position() + &arm;
+        assert!(anchor.magnitude() < 0.02, "anchor = {:?}", anchor);
+        assert!(object.position().z < 0.0);
+    }
+
+    #[test]
+    fn test_hinged_door_turns_about_its_axis() {
+        let mut world = World::new(Vector::new(0.0, 0.0, -10.0));
+        let door = add_box(
+            &mut world,
+            &Vector::new(0.5, 0.0, 0.5),
+            &Vector::new(0.0, 1.0, 0.0),
+        );
+        world.add_joint(
+            None,
+            door,
+            &Joint::hinge(&Vector::new(0.0, 0.0, 0.5), &Vector::new(0.0, 0.0, 1.0)),
+        );
+        run(&mut world, 100);
+        let object = world.get(door).unwrap();
+        let r = object.orientation().to_rotation_matrix();
+        // still upright, turned towards y and hanging from the hinge
+        assert!(
+            (r.column(2).z - 1.0).abs() < 1e-3,
+            "z axis = {:?}",
+            r.column(2)
+        );
+        assert!(r.column(0).y > 0.3);
+        let hinge_side = object.position() - &(&r * &Vector::new(0.5, 0.0, 0.0));
+        assert!((&hinge_side - &Vector::new(0.0, 0.0, 0.5)).magnitude() < 0.02);
+    }
+
+    #[test]
+    fn test_slider_only_moves_along_axis() {
+        let mut world = World::new(Vector::new(0.0, 0.0, -10.0));
+        let b = add_box(&mut world, &Vector::zero(), &Vector::new(1.0, 1.0, 0.0));
+        world.add_joint(
+            None,
+            b,
+            &Joint::slider(&Vector::zero(), &Vector::new(1.0, 0.0, 0.0)),
+        );
+        run(&mut world, 100);
+        let object = world.get(b).unwrap();
+        let x = object.position();
+        assert!(x.y.abs() < 0.02 && x.z.abs() < 0.02, "x = {:?}", x);
+        assert!((x.x - 1.0).abs() < 0.05, "x = {:?}", x);
+        assert!(object.angular_velocity().magnitude() < 1e-3);
+    }
+
+    #[test]
+    fn test_fixed_joint_carries_body() {
+        let mut world = World::new(Vector::new(0.0, 0.0, -10.0));
+        let a = add_box(&mut world, &Vector::zero(), &Vector::zero());
+        let b = add_box(&mut world, &Vector::new(1.0, 0.0, 0.0), &Vector::zero());
+        world.add_joint(None, a, &Joint::ball(&Vector::new(0.0, 0.0, 0.5)));
+        world.add_joint(Some(a), b, &Joint::fixed(&Vector::new(0.5, 0.0, 0.0)));
+        run(&mut world, 100);
+        let (a, b) = (world.get(a).unwrap(), world.get(b).unwrap());
+        // b stays one unit along a's x axis, with the same orientation
+        let r = a.orientation().to_rotation_matrix();
+        let offset = &(&r * &Vector::new(1.0, 0.0, 0.0)) - &(b.position() - &a.position());
+        assert!(offset.magnitude() < 0.02, "offset = {:?}", offset);
+        let turn = &a.orientation().conj() * &b.orientation();
+        assert!(turn.v.magnitude() < 0.01);
+    }
+
+    #[test]
+    fn test_joints_go_with_their_bodies() {
+        let mut world = World::new(Vector::zero());
+        let a = add_box(&mut world, &Vector::zero(), &Vector::zero());
+        let b = add_box(&mut world, &Vector::new(2.0, 0.0, 0.0), &Vector::zero());
+        let joint = world.add_joint(Some(a), b, &Joint::ball(&Vector::new(1.0, 0.0, 0.0)));
+        let other = world
+            .add_joint(None, a, &Joint::fixed(&Vector::zero()))
+            .unwrap();
+        assert!(world.remove_joint(other));
+        assert!(!world.remove_joint(other));
+        world.remove(b);
+        assert!(!world.remove_joint(joint.unwrap()));
+        assert!(world
+            .add_joint(Some(a), b, &Joint::fixed(&Vector::zero()))
+            .is_none());
+    }
+
+    // A unit cube hinged about z through its center, or sliding along x, in zero gravity
+    fn actuated(joint: Joint, v: &Vector, l: &Vector) -> (World, BodyHandle, JointHandle) {
+        let mut world = World::new(Vector::zero());
+        let b = world.add(
+            &RigidBox::new(1.0, 1.0, 1.0, 1.0),
+            &Vector::zero(),
+            &identity(),
+            v,
+            l,
+        );
+        let handle = world.add_joint(None, b, &joint).unwrap();
+        (world, b, handle)
+    }
+
+    fn hinge() -> Joint {
+        Joint::hinge(&Vector::zero(), &Vector::new(0.0, 0.0, 1.0))
+    }
+
+    #[test]
+    fn test_velocity_motor() {
+        let motor = JointMotor::Velocity {
+            speed: 2.0,
+            max_force: 100.0,
+        };
+        let (mut world, b, handle) =
+            actuated(hinge().with_motor(motor), &Vector::zero(), &Vector::zero());
+        run(&mut world, 50);
+        assert!((world.get(b).unwrap().angular_velocity().z - 2.0).abs() < 1e-3);
+        let angle = world.joint_position(handle).unwrap();
+        assert!((angle - 1.0).abs() < 0.05, "angle = {}", angle);
+    }
+
+    #[test]
+    fn test_motor_torque_is_limited() {
+        // a unit cube has I = 1/6 about z, so 0.1 Nm gives 0.6 rad/s^2
+        let motor = JointMotor::Velocity {
+            speed: 2.0,
+            max_force: 0.1,
+        };
+        let (mut world, b, _) =
+            actuated(hinge().with_motor(motor), &Vector::zero(), &Vector::zero());
+        run(&mut world, 100);
+        let w = world.get(b).unwrap().angular_velocity().z;
+        assert!((w - 0.6).abs() < 0.01, "w = {}", w);
+    }
+
+    #[test]
+    fn test_servo_reaches_target() {
+        let motor = JointMotor::Servo {
+            target: 1.0,
+            stiffness: 20.0,
+            damping: 5.0,
+            max_force: 100.0,
+        };
+        let (mut world, b, handle) =
+            actuated(hinge().with_motor(motor), &Vector::zero(), &Vector::zero());
+        run(&mut world, 300);
+        let angle = world.joint_position(handle).unwrap();
+        assert!((angle - 1.0).abs() < 0.01, "angle = {}", angle);
+        assert!(world.get(b).unwrap().angular_velocity().magnitude() < 0.01);
+        // switching the motor off leaves the hinge free
+        assert!(world.set_joint_motor(handle, None));
+        world.remove(b);
+        assert!(!world.set_joint_motor(handle, Some(motor)));
+    }
+
+    #[test]
+    fn test_slider_stops_at_limit() {
+        let limit = JointLimit {
+            lower: -0.5,
+            upper: 0.5,
+            restitution: 0.0,
+        };
+        let slider = Joint::slider(&Vector::zero(), &Vector::new(1.0, 0.0, 0.0)).with_limit(limit);
+        let (mut world, b, handle) = actuated(slider, &Vector::new(1.0, 0.0, 0.0), &Vector::zero());
+        run(&mut world, 100);
+        let offset = world.joint_position(handle).unwrap();
+        assert!((offset - 0.5).abs() < 0.02, "offset = {}", offset);
+        assert!(world.get(b).unwrap().linear_velocity().magnitude() < 0.05);
+    }
+
+    #[test]
+    fn test_hinge_bounces_off_limit() {
+        let limit = JointLimit {
+            lower: -0.3,
+            upper: 0.3,
+            restitution: 0.5,
+        };
+        // spinning at 2 rad/s about z
+        let l = Vector::new(0.0, 0.0, 2.0 / 6.0);
+        let (mut world, b, handle) = actuated(hinge().with_limit(limit), &Vector::zero(), &l);
+        run(&mut world, 25);
+        let w = world.get(b).unwrap().angular_velocity().z;
+        assert!((w + 1.0).abs() < 0.1, "w = {}", w);
+        let angle = world.joint_position(handle).unwrap();
+        assert!(angle.abs() < 0.32, "angle = {}", angle);
+    }
+}
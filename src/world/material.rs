@@ -0,0 +1,132 @@
+// How the values of a property of two touching materials make the value for the
+// contact. When the two materials ask for different rules, the later one in this
+// list wins.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum CombineRule {
+    #[default]
+    Average,
+    Min,
+    Multiply,
+    Max,
+}
+
+impl CombineRule {
+    pub fn combine(self, a: f64, b: f64) -> f64 {
+        match self {
+            CombineRule::Average => 0.5 * (a + b),
+            CombineRule::Min => a.min(b),
+            CombineRule::Multiply => a * b,
+            CombineRule::Max => a.max(b),
+        }
+    }
+}
+
+// Surface and bulk properties of a body or collider
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Material {
+    // friction coefficient of contacts which do not slide
+    pub static_friction: f64,
+    // friction coefficient of sliding contacts
+    pub dynamic_friction: f64,
+    pub restitution: f64,
+    // resisting torque per unit of normal force, a length
+    pub rolling_friction: f64,
+    pub density: f64,
+    pub friction_combine: CombineRule,
+    pub restitution_combine: CombineRule,
+    pub rolling_friction_combine: CombineRule,
+}
+
+impl Material {
+    pub const fn new(friction: f64, restitution: f64, density: f64) -> Self {
+        Self {
+            static_friction: friction,
+            dynamic_friction: friction,
+            restitution,
+            rolling_friction: 0.0,
+            density,
+            friction_combine: CombineRule::Average,
+            restitution_combine: CombineRule::Average,
+            rolling_friction_combine: CombineRule::Average,
+        }
+    }
+    pub const fn with_friction(mut self, static_friction: f64, dynamic_friction: f64) -> Self {
+        self.static_friction = static_friction;
+        self.dynamic_friction = dynamic_friction;
+        self
+    }
+    pub const fn with_rolling_friction(mut self, rolling_friction: f64) -> Self {
+        self.rolling_friction = rolling_friction;
+        self
+    }
+    pub const fn with_friction_combine(mut self, rule: CombineRule) -> Self {
+        self.friction_combine = rule;
+        self
+    }
+    pub const fn with_restitution_combine(mut self, rule: CombineRule) -> Self {
+        self.restitution_combine = rule;
+        self
+    }
+    pub const fn with_rolling_friction_combine(mut self, rule: CombineRule) -> Self {
+        self.rolling_friction_combine = rule;
+        self
+    }
+    // Coefficients of a contact between the two materials
+    pub fn combine(&self, other: &Self) -> ContactMaterial {
+        let friction = self.friction_combine.max(other.friction_combine);
+        let restitution = self.restitution_combine.max(other.restitution_combine);
+        let rolling = self
+            .rolling_friction_combine
+            .max(other.rolling_friction_combine);
+        ContactMaterial {
+            static_friction: friction.combine(self.static_friction, other.static_friction),
+            dynamic_friction: friction.combine(self.dynamic_friction, other.dynamic_friction),
+            restitution: restitution.combine(self.restitution, other.restitution),
+            rolling_friction: rolling.combine(self.rolling_friction, other.rolling_friction),
+        }
+    }
+}
+
+impl Default for Material {
+    fn default() -> Self {
+        Self::new(0.5, 0.2, 1.0)
+    }
+}
+
+// Coefficients the solver uses for one contact
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ContactMaterial {
+    pub static_friction: f64,
+    pub dynamic_friction: f64,
+    pub restitution: f64,
+    pub rolling_friction: f64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::approx_eq::{assert_approx_eq, ApproxEq};
+
+    #[test]
+    fn test_combine_rules() {
+        assert_approx_eq!(CombineRule::Average.combine(0.2, 0.6), 0.4);
+        assert_approx_eq!(CombineRule::Min.combine(0.2, 0.6), 0.2);
+        assert_approx_eq!(CombineRule::Multiply.combine(0.2, 0.6), 0.12);
+        assert_approx_eq!(CombineRule::Max.combine(0.2, 0.6), 0.6);
+    }
+
+    #[test]
+    fn test_stronger_rule_wins() {
+        let ice = Material::new(0.05, 0.1, 0.9).with_friction_combine(CombineRule::Min);
+        let rubber = Material::new(1.0, 0.8, 1.2)
+            .with_friction(1.0, 0.8)
+            .with_restitution_combine(CombineRule::Max);
+        let contact = ice.combine(&rubber);
+        assert_approx_eq!(contact.static_friction, 0.05);
+        assert_approx_eq!(contact.dynamic_friction, 0.05);
+        assert_approx_eq!(contact.restitution, 0.8);
+        assert_eq!(contact, rubber.combine(&ice));
+        let plain = Material::default().combine(&rubber);
+        assert_approx_eq!(plain.dynamic_friction, 0.65);
+    }
+}
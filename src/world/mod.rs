@@ -1,3 +1,30 @@
-mod rigid_body;
-pub(crate) mod rigid_box;
-pub(crate) mod world;
+pub mod aabb;
+pub mod body_type;
+pub mod broad_phase;
+pub mod collision;
+pub mod collision_groups;
+pub mod compound_body;
+pub mod contact_event;
+pub mod contact_hooks;
+pub mod handle;
+pub mod integrator;
+pub mod island;
+pub mod joint;
+pub mod material;
+pub mod polyhedron;
+pub mod query;
+pub mod rigid_body;
+pub mod rigid_body_state;
+pub mod rigid_box;
+pub mod rigid_capsule;
+pub mod rigid_cone;
+pub mod rigid_convex_hull;
+pub mod rigid_cylinder;
+pub mod rigid_sphere;
+pub mod rigid_tri_mesh;
+pub mod sensor;
+pub mod shape;
+pub mod solver;
+pub mod static_collider;
+#[allow(clippy::module_inception)]
+pub mod world;
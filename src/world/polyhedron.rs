@@ -0,0 +1,172 @@
+use super::material::Material;
+use crate::math::matrix::Matrix;
+use crate::math::vector::Vector;
+
+// Triangle mesh and mass properties shared by the convex hull and triangle mesh
+// bodies, moved so the center of mass is at the origin
+pub(crate) struct Polyhedron {
+    pub(crate) vertices: Vec<Vector>,
+    pub(crate) triangles: Vec<[usize; 3]>,
+    // center of mass in the coordinates of the input vertices
+    pub(crate) centroid: Vector,
+    pub(crate) mass: f64,
+    pub(crate) inertia: Matrix,
+    pub(crate) material: Option<Material>,
+}
+
+impl Polyhedron {
+    pub(crate) fn new(vertices: &[Vector], triangles: &[[usize; 3]], density: f64) -> Self {
+        let props = mass_properties(vertices, triangles, density);
+        Self {
+            vertices: vertices.iter().map(|v| v - &props.center).collect(),
+            triangles: triangles.to_vec(),
+            centroid: props.center,
+            mass: props.mass,
+            inertia: props.inertia,
+            material: None,
+        }
+    }
+    pub(crate) fn with_material(
+        vertices: &[Vector],
+        triangles: &[[usize; 3]],
+        material: Material,
+    ) -> Self {
+        Self {
+            material: Some(material),
+            ..Self::new(vertices, triangles, material.density)
+        }
+    }
+}
+
+pub(crate) struct MassProperties {
+    pub(crate) mass: f64,
+    pub(crate) center: Vector,
+    // about `center`
+    pub(crate) inertia: Matrix,
+}
+
+// Mass properties of the solid bounded by a closed triangle mesh. By the divergence
+// theorem the volume integrals are sums over tetrahedra spanned by the origin and
+// each triangle; either consistent winding is accepted. Panics unless the mesh
+// encloses some volume, as empty, flat and open meshes may not.
+pub(crate) fn mass_properties(
+    vertices: &[Vector],
+    triangles: &[[usize; 3]],
+    density: f64,
+) -> MassProperties {
+    let mut volume = 0.0;
+    let mut moment = Vector::zero();
+    // integral of x x^T over the volume
+    let mut covariance = Matrix::new([0.0; 9]);
+    for [i, j, k] in triangles {
+        let (a, b, c) = (&vertices[*i], &vertices[*j], &vertices[*k]);
+        // six times the signed volume of the tetrahedron
+        let det = a.dot(&b.cross(c));
+        let sum = a + b + c;
+        volume += det / 6.0;
+        moment = moment + &(&sum * (det / 24.0));
+        let products = Matrix::outer(a, a) + &Matrix::outer(b, b) + &Matrix::outer(c, c);
+        covariance = covariance + &(&(products + &Matrix::outer(&sum, &sum)) * (det / 120.0));
+    }
+    if volume < 0.0 {
+        volume = -volume;
+        moment = -moment;
+        covariance = &covariance * -1.0;
+    }
+    assert!(
+        volume > 0.0 && volume.is_finite(),
+        "mesh encloses no volume: {}",
+        volume
+    );
+    let center = &moment / volume;
+    let covariance = &covariance - &(&Matrix::outer(&center, &center) * volume);
+    let inertia = &(&Matrix::diagonal(1.0, 1.0, 1.0) * covariance.trace()) - &covariance;
+    MassProperties {
+        mass: density * volume,
+        center,
+        inertia: &inertia * density,
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::math::approx_eq::{assert_approx_eq, ApproxEq};
+
+    // Axis aligned box with the given corners, outward winding
+    pub(crate) fn cuboid(min: &Vector, max: &Vector) -> (Vec<Vector>, Vec<[usize; 3]>) {
+        let vertices = (0..8)
+            .map(|i| {
+                Vector::new(
+                    if i & 1 == 0 { min.x } else { max.x },
+                    if i & 2 == 0 { min.y } else { max.y },
+                    if i & 4 == 0 { min.z } else { max.z },
+                )
+            })
+            .collect();
+        let triangles = vec![
+            [0, 2, 1],
+            [1, 2, 3],
+            [4, 5, 6],
+            [5, 7, 6],
+            [0, 1, 4],
+            [1, 5, 4],
+            [2, 6, 3],
+            [3, 6, 7],
+            [0, 4, 2],
+            [2, 4, 6],
+            [1, 3, 5],
+            [3, 7, 5],
+        ];
+        (vertices, triangles)
+    }
+
+    pub(crate) fn tetrahedron() -> (Vec<Vector>, Vec<[usize; 3]>) {
+        let vertices = vec![
+            Vector::zero(),
+            Vector::new(1.0, 0.0, 0.0),
+            Vector::new(0.0, 1.0, 0.0),
+            Vector::new(0.0, 0.0, 1.0),
+        ];
+        let triangles = vec![[0, 2, 1], [0, 1, 3], [0, 3, 2], [1, 2, 3]];
+        (vertices, triangles)
+    }
+
+    #[test]
+    fn test_box_mass_properties() {
+        let (vertices, triangles) =
+            cuboid(&Vector::new(1.0, 2.0, 3.0), &Vector::new(3.0, 3.0, 6.0));
+        let props = mass_properties(&vertices, &triangles, 2.0);
+        assert_approx_eq!(props.mass, 12.0);
+        assert_approx_eq!(props.center, Vector::new(2.0, 2.5, 4.5));
+        // m (b^2 + c^2) / 12 and so on
+        assert_approx_eq!(props.inertia, Matrix::diagonal(10.0, 13.0, 5.0));
+    }
+
+    #[test]
+    fn test_tetrahedron_products_of_inertia() {
+        let (vertices, triangles) = tetrahedron();
+        let props = mass_properties(&vertices, &triangles, 1.0);
+        assert_approx_eq!(props.mass, 1.0 / 6.0);
+        assert_approx_eq!(props.center, Vector::new(0.25, 0.25, 0.25));
+        let (d, p) = (1.0 / 80.0, 1.0 / 480.0);
+        assert_approx_eq!(props.inertia, Matrix::new([d, p, p, p, d, p, p, p, d]));
+    }
+
+    #[test]
+    #[should_panic(expected = "mesh encloses no volume")]
+    fn test_flat_mesh_is_rejected() {
+        let (vertices, _) = tetrahedron();
+        // the base of the tetrahedron, seen from both sides
+        mass_properties(&vertices, &[[0, 2, 1], [0, 1, 2]], 1.0);
+    }
+
+    #[test]
+    fn test_inverted_winding() {
+        let (vertices, triangles) = tetrahedron();
+        let flipped: Vec<[usize; 3]> = triangles.iter().map(|[i, j, k]| [*i, *k, *j]).collect();
+        let props = mass_properties(&vertices, &flipped, 1.0);
+        assert_approx_eq!(props.mass, 1.0 / 6.0);
+        assert_approx_eq!(props.inertia.trace(), 3.0 / 80.0);
+    }
+}
@@ -0,0 +1,82 @@
+use super::body_type::BodyType;
+use super::collision_groups::CollisionGroups;
+use super::handle::BodyHandle;
+use super::static_collider::{StaticCollider, StaticHandle};
+use super::world::WorldObject;
+use crate::math::vector::Vector;
+
+// Anything a query can find
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Collider {
+    Body(BodyHandle),
+    Static(StaticHandle),
+}
+
+// Which colliders a query considers, by default all of them
+#[derive(Debug, Clone, Default)]
+pub struct QueryFilter {
+    // the query acts as a collider of these groups
+    groups: CollisionGroups,
+    exclude_statics: bool,
+    exclude_sensors: bool,
+    excluded_types: Vec<BodyType>,
+    excluded_bodies: Vec<BodyHandle>,
+}
+
+impl QueryFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn with_groups(mut self, groups: CollisionGroups) -> Self {
+        self.groups = groups;
+        self
+    }
+    pub fn without_statics(mut self) -> Self {
+        self.exclude_statics = true;
+        self
+    }
+    pub fn without_sensors(mut self) -> Self {
+        self.exclude_sensors = true;
+        self
+    }
+    pub fn without_body_type(mut self, body_type: BodyType) -> Self {
+        self.excluded_types.push(body_type);
+        self
+    }
+    // Typically the body casting the ray
+    pub fn excluding(mut self, handle: BodyHandle) -> Self {
+        self.excluded_bodies.push(handle);
+        self
+    }
+    pub(crate) fn accepts_static(&self, collider: &StaticCollider) -> bool {
+        let excluded = self.exclude_statics || self.exclude_sensors && collider.sensor;
+        !excluded && self.groups.interacts_with(&collider.groups)
+    }
+    pub(crate) fn accepts_body(&self, body: &WorldObject) -> bool {
+        let excluded = self.excluded_types.contains(&body.body_type())
+            || self.excluded_bodies.contains(&body.handle())
+            || self.exclude_sensors && body.is_sensor();
+        !excluded && self.groups.interacts_with(&body.collision_groups())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RayHit {
+    pub collider: Collider,
+    pub point: Vector,
+    // outward normal of the surface hit
+    pub normal: Vector,
+    // from the origin of the ray
+    pub distance: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct ShapeHit {
+    pub collider: Collider,
+    // where the swept shape first touches the collider
+    pub point: Vector,
+    // outward normal of the collider there
+    pub normal: Vector,
+    // travelled by the shape
+    pub distance: f64,
+}
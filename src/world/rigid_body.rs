@@ -1,6 +1,20 @@
+use super::material::Material;
+use super::shape::Shape;
 use crate::math::matrix::Matrix;
+use crate::math::vector::Vector;
 
 pub trait RigidBody {
     fn mass(&self) -> f64;
+    // About the center of mass, along the body axes; any symmetric tensor
     fn inertia_tensor(&self) -> Matrix;
+    fn shape(&self) -> Shape;
+    // In body coordinates, which are also those of the shape
+    fn center_of_mass(&self) -> Vector {
+        Vector::zero()
+    }
+    // Given to the body when it is added to a world. Bodies made `with_material`
+    // keep the material and take their density from it.
+    fn material(&self) -> Option<Material> {
+        None
+    }
 }
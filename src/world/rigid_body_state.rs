@@ -0,0 +1,63 @@
+use crate::math::matrix::Matrix;
+use crate::math::quaternion::Quaternion;
+use crate::math::vector::Vector;
+
+#[derive(Clone)]
+pub struct RigidBodyState {
+    // position
+    pub(crate) x: Vector,
+    // orientation
+    pub(crate) q: Quaternion,
+    // linear momentum
+    pub(crate) p: Vector,
+    // angular momentum
+    pub(crate) l: Vector,
+    // orientation matrix
+    pub(crate) r: Matrix,
+    // linear velocity
+    pub(crate) v: Vector,
+    // angular velocity
+    pub(crate) w: Vector,
+}
+
+impl RigidBodyState {
+    pub(crate) fn new(
+        x: &Vector,
+        q: &Quaternion,
+        p: &Vector,
+        l: &Vector,
+        inv_mass: f64,
+        inv_inertia: &Matrix,
+    ) -> Self {
+        let q = q.normalize(); // always necessary?
+        let r = q.to_rotation_matrix();
+        let v = p * inv_mass;
+        let w = &r * (inv_inertia * (&r.transpose() * l));
+        Self {
+            x: x.clone(),
+            q,
+            p: p.clone(),
+            l: l.clone(),
+            r,
+            v,
+            w,
+        }
+    }
+    // Inverse inertia tensor in world coordinates, R I^-1 R^T
+    pub(crate) fn inv_inertia_world(&self, inv_inertia: &Matrix) -> Matrix {
+        &(&self.r * inv_inertia) * &self.r.transpose()
+    }
+    // Applies `impulse` at world position `point` and updates the velocities
+    pub(crate) fn apply_impulse(
+        &mut self,
+        impulse: &Vector,
+        point: &Vector,
+        inv_mass: f64,
+        inv_inertia: &Matrix,
+    ) {
+        self.p = &self.p + impulse;
+        self.l = &self.l + &(point - &self.x).cross(impulse);
+        self.v = &self.p * inv_mass;
+        self.w = &self.inv_inertia_world(inv_inertia) * &self.l;
+    }
+}
@@ -1,12 +1,16 @@
+use super::material::Material;
 use super::rigid_body::RigidBody;
-use crate::math::sq;
+use super::shape::Shape;
 use crate::math::matrix::Matrix;
+use crate::math::sq;
+use crate::math::vector::Vector;
 
 pub struct RigidBox {
     x: f64,
     y: f64,
     z: f64,
     mass: f64,
+    material: Option<Material>,
 }
 
 impl RigidBox {
@@ -16,6 +20,13 @@ impl RigidBox {
             y,
             z,
             mass: x * y * z * density,
+            material: None,
+        }
+    }
+    pub fn with_material(x: f64, y: f64, z: f64, material: Material) -> Self {
+        Self {
+            material: Some(material),
+            ..Self::new(x, y, z, material.density)
         }
     }
 }
@@ -38,4 +49,12 @@ impl RigidBody for RigidBox {
             s * (sq(self.x) + sq(self.y)),
         ])
     }
+    fn shape(&self) -> Shape {
+        Shape::Box {
+            half_extents: Vector::new(0.5 * self.x, 0.5 * self.y, 0.5 * self.z),
+        }
+    }
+    fn material(&self) -> Option<Material> {
+        self.material
+    }
 }
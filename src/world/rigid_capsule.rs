@@ -0,0 +1,97 @@
+use super::material::Material;
+use super::rigid_body::RigidBody;
+use super::shape::Shape;
+use crate::math::matrix::Matrix;
+use crate::math::sq;
+use std::f64::consts::PI;
+
+// Cylinder of the given height along z, capped by two hemispheres
+pub struct RigidCapsule {
+    radius: f64,
+    height: f64,
+    // mass of the cylinder and of both caps together
+    cylinder_mass: f64,
+    caps_mass: f64,
+    material: Option<Material>,
+}
+
+impl RigidCapsule {
+    pub fn new(radius: f64, height: f64, density: f64) -> Self {
+        Self {
+            radius,
+            height,
+            cylinder_mass: PI * sq(radius) * height * density,
+            caps_mass: 4.0 / 3.0 * PI * radius * sq(radius) * density,
+            material: None,
+        }
+    }
+    pub fn with_material(radius: f64, height: f64, material: Material) -> Self {
+        Self {
+            material: Some(material),
+            ..Self::new(radius, height, material.density)
+        }
+    }
+}
+
+impl RigidBody for RigidCapsule {
+    fn mass(&self) -> f64 {
+        self.cylinder_mass + self.caps_mass
+    }
+    fn inertia_tensor(&self) -> Matrix {
+        let (r, h) = (self.radius, self.height);
+        let (mc, ms) = (self.cylinder_mass, self.caps_mass);
+        // hemispheres moved from the center to the ends of the cylinder
+        let ixy = mc * (sq(h) / 12.0 + sq(r) / 4.0)
+            + ms * (0.4 * sq(r) + sq(h) / 4.0 + 3.0 * h * r / 8.0);
+        let iz = mc * sq(r) / 2.0 + ms * 0.4 * sq(r);
+        Matrix::new([ixy, 0.0, 0.0, 0.0, ixy, 0.0, 0.0, 0.0, iz])
+    }
+    fn shape(&self) -> Shape {
+        Shape::Capsule {
+            radius: self.radius,
+            half_height: 0.5 * self.height,
+        }
+    }
+    fn material(&self) -> Option<Material> {
+        self.material
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::approx_eq::{assert_approx_eq, ApproxEq};
+    use crate::world::rigid_cylinder::RigidCylinder;
+    use crate::world::rigid_sphere::RigidSphere;
+
+    #[test]
+    fn test_capsule_mass_properties() {
+        let capsule = RigidCapsule::new(1.0, 2.0, 3.0);
+        // cylinder 6 pi plus sphere 4 pi
+        assert_approx_eq!(capsule.mass(), 10.0 * PI);
+        let ixy = 6.0 * PI * (4.0 / 12.0 + 0.25) + 4.0 * PI * (0.4 + 1.0 + 0.75);
+        let iz = 3.0 * PI + 1.6 * PI;
+        assert_approx_eq!(
+            capsule.inertia_tensor(),
+            Matrix::new([ixy, 0.0, 0.0, 0.0, ixy, 0.0, 0.0, 0.0, iz])
+        );
+    }
+
+    #[test]
+    fn test_capsule_without_cylinder_is_a_sphere() {
+        let capsule = RigidCapsule::new(1.5, 0.0, 2.0);
+        let sphere = RigidSphere::new(1.5, 2.0);
+        assert_approx_eq!(capsule.mass(), sphere.mass());
+        assert_approx_eq!(capsule.inertia_tensor(), sphere.inertia_tensor());
+    }
+
+    #[test]
+    fn test_capsule_axial_moment() {
+        // about the axis the caps add like a sphere to the cylinder
+        let capsule = RigidCapsule::new(0.5, 4.0, 1.0);
+        let cylinder = RigidCylinder::new(0.5, 4.0, 1.0);
+        let sphere = RigidSphere::new(0.5, 1.0);
+        let iz = cylinder.inertia_tensor().column(2).z + sphere.inertia_tensor().column(2).z;
+        assert_approx_eq!(capsule.inertia_tensor().column(2).z, iz);
+    }
+}
@@ -0,0 +1,81 @@
+use super::material::Material;
+use super::rigid_body::RigidBody;
+use super::shape::Shape;
+use crate::math::matrix::Matrix;
+use crate::math::sq;
+use std::f64::consts::PI;
+
+// Solid cone with its apex along +z, positioned by its center of mass a quarter
+// of the height above the base
+pub struct RigidCone {
+    radius: f64,
+    height: f64,
+    mass: f64,
+    material: Option<Material>,
+}
+
+impl RigidCone {
+    pub fn new(radius: f64, height: f64, density: f64) -> Self {
+        Self {
+            radius,
+            height,
+            mass: PI * sq(radius) * height * density / 3.0,
+            material: None,
+        }
+    }
+    pub fn with_material(radius: f64, height: f64, material: Material) -> Self {
+        Self {
+            material: Some(material),
+            ..Self::new(radius, height, material.density)
+        }
+    }
+}
+
+impl RigidBody for RigidCone {
+    fn mass(&self) -> f64 {
+        self.mass
+    }
+    fn inertia_tensor(&self) -> Matrix {
+        let ixy = self.mass * (3.0 / 20.0 * sq(self.radius) + 3.0 / 80.0 * sq(self.height));
+        let iz = 0.3 * self.mass * sq(self.radius);
+        Matrix::new([ixy, 0.0, 0.0, 0.0, ixy, 0.0, 0.0, 0.0, iz])
+    }
+    fn shape(&self) -> Shape {
+        Shape::Cone {
+            radius: self.radius,
+            height: self.height,
+        }
+    }
+    fn material(&self) -> Option<Material> {
+        self.material
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::approx_eq::{assert_approx_eq, ApproxEq};
+
+    #[test]
+    fn test_cone_mass_properties() {
+        let cone = RigidCone::new(2.0, 4.0, 3.0);
+        // m = pi r^2 h rho / 3, Izz = 3/10 m r^2, Ixx = m (3/20 r^2 + 3/80 h^2)
+        assert_approx_eq!(cone.mass(), 16.0 * PI);
+        let ixy = 16.0 * PI * (0.6 + 0.6);
+        let iz = 0.3 * 16.0 * PI * 4.0;
+        assert_approx_eq!(
+            cone.inertia_tensor(),
+            Matrix::new([ixy, 0.0, 0.0, 0.0, ixy, 0.0, 0.0, 0.0, iz])
+        );
+    }
+
+    #[test]
+    fn test_cone_moment_about_apex() {
+        // parallel axis theorem back to the apex gives m (3/20 r^2 + 3/5 h^2)
+        let (r, h) = (1.0, 2.0);
+        let cone = RigidCone::new(r, h, 1.0);
+        let m = cone.mass();
+        let apex = cone.inertia_tensor().column(0).x + m * sq(0.75 * h);
+        assert_approx_eq!(apex, m * (3.0 / 20.0 * sq(r) + 3.0 / 5.0 * sq(h)));
+    }
+}
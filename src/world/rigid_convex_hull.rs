@@ -0,0 +1,92 @@
+use super::material::Material;
+use super::polyhedron::Polyhedron;
+use super::rigid_body::RigidBody;
+use super::shape::Shape;
+use crate::math::matrix::Matrix;
+use crate::math::vector::Vector;
+
+// Convex polyhedron given by its vertices and triangulated faces, moved so its
+// center of mass is at the origin
+pub struct RigidConvexHull(Polyhedron);
+
+impl RigidConvexHull {
+    pub fn new(vertices: &[Vector], triangles: &[[usize; 3]], density: f64) -> Self {
+        Self(Polyhedron::new(vertices, triangles, density))
+    }
+    pub fn with_material(
+        vertices: &[Vector],
+        triangles: &[[usize; 3]],
+        material: Material,
+    ) -> Self {
+        Self(Polyhedron::with_material(vertices, triangles, material))
+    }
+    // Center of mass in the coordinates of the input vertices, subtracted from them
+    pub fn centroid(&self) -> &Vector {
+        &self.0.centroid
+    }
+}
+
+impl RigidBody for RigidConvexHull {
+    fn mass(&self) -> f64 {
+        self.0.mass
+    }
+    fn inertia_tensor(&self) -> Matrix {
+        self.0.inertia.clone()
+    }
+    fn shape(&self) -> Shape {
+        Shape::ConvexHull {
+            vertices: self.0.vertices.clone(),
+            triangles: self.0.triangles.clone(),
+        }
+    }
+    fn material(&self) -> Option<Material> {
+        self.0.material
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::approx_eq::{assert_approx_eq, ApproxEq};
+    use crate::math::quaternion::Quaternion;
+    use crate::world::polyhedron::tests::cuboid;
+    use crate::world::rigid_box::RigidBox;
+
+    #[test]
+    fn test_hull_of_box_matches_rigid_box() {
+        let (vertices, triangles) =
+            cuboid(&Vector::new(1.0, 1.0, 1.0), &Vector::new(5.0, 3.0, 4.0));
+        let hull = RigidConvexHull::new(&vertices, &triangles, 1.5);
+        let rbox = RigidBox::new(4.0, 2.0, 3.0, 1.5);
+        assert_approx_eq!(hull.mass(), rbox.mass());
+        assert_approx_eq!(hull.inertia_tensor(), rbox.inertia_tensor());
+        assert_approx_eq!(hull.centroid(), &Vector::new(3.0, 2.0, 2.5));
+    }
+
+    #[test]
+    fn test_hull_is_recentered() {
+        let (vertices, triangles) =
+            cuboid(&Vector::new(1.0, 1.0, 1.0), &Vector::new(5.0, 3.0, 4.0));
+        let hull = RigidConvexHull::new(&vertices, &triangles, 1.0);
+        match hull.shape() {
+            Shape::ConvexHull { vertices, .. } => {
+                assert_approx_eq!(vertices[0], Vector::new(-2.0, -1.0, -1.5));
+                assert_approx_eq!(vertices[7], Vector::new(2.0, 1.0, 1.5));
+            }
+            _ => panic!("not a convex hull"),
+        }
+    }
+
+    #[test]
+    fn test_rotated_box_has_products_of_inertia() {
+        // I' = R I R^T for the box rotated by R
+        let (vertices, triangles) =
+            cuboid(&Vector::new(-2.0, -1.0, -1.5), &Vector::new(2.0, 1.0, 1.5));
+        let r = Quaternion::from_rotation(&Vector::new(1.0, 2.0, 2.0).normalize(), 0.7)
+            .to_rotation_matrix();
+        let rotated: Vec<Vector> = vertices.iter().map(|v| &r * v).collect();
+        let hull = RigidConvexHull::new(&rotated, &triangles, 1.0);
+        let expected = &(&r * &RigidBox::new(4.0, 2.0, 3.0, 1.0).inertia_tensor()) * &r.transpose();
+        assert_approx_eq!(hull.inertia_tensor(), expected);
+    }
+}
@@ -0,0 +1,81 @@
+use super::material::Material;
+use super::rigid_body::RigidBody;
+use super::shape::Shape;
+use crate::math::matrix::Matrix;
+use crate::math::sq;
+use std::f64::consts::PI;
+
+// Solid cylinder with its axis along z
+pub struct RigidCylinder {
+    radius: f64,
+    height: f64,
+    mass: f64,
+    material: Option<Material>,
+}
+
+impl RigidCylinder {
+    pub fn new(radius: f64, height: f64, density: f64) -> Self {
+        Self {
+            radius,
+            height,
+            mass: PI * sq(radius) * height * density,
+            material: None,
+        }
+    }
+    pub fn with_material(radius: f64, height: f64, material: Material) -> Self {
+        Self {
+            material: Some(material),
+            ..Self::new(radius, height, material.density)
+        }
+    }
+}
+
+impl RigidBody for RigidCylinder {
+    fn mass(&self) -> f64 {
+        self.mass
+    }
+    fn inertia_tensor(&self) -> Matrix {
+        let ixy = self.mass * (3.0 * sq(self.radius) + sq(self.height)) / 12.0;
+        let iz = 0.5 * self.mass * sq(self.radius);
+        Matrix::new([ixy, 0.0, 0.0, 0.0, ixy, 0.0, 0.0, 0.0, iz])
+    }
+    fn shape(&self) -> Shape {
+        Shape::Cylinder {
+            radius: self.radius,
+            half_height: 0.5 * self.height,
+        }
+    }
+    fn material(&self) -> Option<Material> {
+        self.material
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::approx_eq::{assert_approx_eq, ApproxEq};
+
+    #[test]
+    fn test_cylinder_mass_properties() {
+        let cylinder = RigidCylinder::new(1.0, 2.0, 2.0);
+        // m = pi r^2 h rho, Ixx = m (3 r^2 + h^2) / 12, Izz = m r^2 / 2
+        assert_approx_eq!(cylinder.mass(), 4.0 * PI);
+        let ixy = 7.0 / 3.0 * PI;
+        let iz = 2.0 * PI;
+        assert_approx_eq!(
+            cylinder.inertia_tensor(),
+            Matrix::new([ixy, 0.0, 0.0, 0.0, ixy, 0.0, 0.0, 0.0, iz])
+        );
+    }
+
+    #[test]
+    fn test_thin_disc_limit() {
+        // a flat disc has Izz = Ixx + Iyy
+        let disc = RigidCylinder::new(3.0, 1e-6, 1.0);
+        let i = disc.inertia_tensor();
+        assert_approx_eq!(
+            i.column(0).x * 2.0 / disc.mass(),
+            i.column(2).z / disc.mass()
+        );
+    }
+}
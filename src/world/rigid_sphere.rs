@@ -0,0 +1,64 @@
+use super::material::Material;
+use super::rigid_body::RigidBody;
+use super::shape::Shape;
+use crate::math::matrix::Matrix;
+use crate::math::sq;
+use std::f64::consts::PI;
+
+pub struct RigidSphere {
+    radius: f64,
+    mass: f64,
+    material: Option<Material>,
+}
+
+impl RigidSphere {
+    pub fn new(radius: f64, density: f64) -> Self {
+        Self {
+            radius,
+            mass: 4.0 / 3.0 * PI * radius * sq(radius) * density,
+            material: None,
+        }
+    }
+    pub fn with_material(radius: f64, material: Material) -> Self {
+        Self {
+            material: Some(material),
+            ..Self::new(radius, material.density)
+        }
+    }
+}
+
+impl RigidBody for RigidSphere {
+    fn mass(&self) -> f64 {
+        self.mass
+    }
+    fn inertia_tensor(&self) -> Matrix {
+        let i = 0.4 * self.mass * sq(self.radius);
+        Matrix::new([i, 0.0, 0.0, 0.0, i, 0.0, 0.0, 0.0, i])
+    }
+    fn shape(&self) -> Shape {
+        Shape::Sphere {
+            radius: self.radius,
+        }
+    }
+    fn material(&self) -> Option<Material> {
+        self.material
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::approx_eq::{assert_approx_eq, ApproxEq};
+
+    #[test]
+    fn test_sphere_mass_properties() {
+        let sphere = RigidSphere::new(2.0, 3.0);
+        // m = 4/3 pi r^3 rho, I = 2/5 m r^2
+        assert_approx_eq!(sphere.mass(), 32.0 * PI);
+        let i = 51.2 * PI;
+        assert_approx_eq!(
+            sphere.inertia_tensor(),
+            Matrix::new([i, 0.0, 0.0, 0.0, i, 0.0, 0.0, 0.0, i])
+        );
+    }
+}
@@ -0,0 +1,95 @@
+use super::material::Material;
+use super::polyhedron::Polyhedron;
+use super::rigid_body::RigidBody;
+use super::shape::Shape;
+use crate::math::matrix::Matrix;
+use crate::math::vector::Vector;
+
+// Solid bounded by a closed, possibly concave, triangle mesh, moved so its
+// center of mass is at the origin. Its mass properties follow the mesh, but it
+// collides and is hit by rays as the convex hull of its vertices, filling any
+// concavity. Concave solids which must collide as such are best built as a
+// `CompoundBody` of convex parts.
+pub struct RigidTriMesh(Polyhedron);
+
+impl RigidTriMesh {
+    pub fn new(vertices: &[Vector], triangles: &[[usize; 3]], density: f64) -> Self {
+        Self(Polyhedron::new(vertices, triangles, density))
+    }
+    pub fn with_material(
+        vertices: &[Vector],
+        triangles: &[[usize; 3]],
+        material: Material,
+    ) -> Self {
+        Self(Polyhedron::with_material(vertices, triangles, material))
+    }
+    // Center of mass in the coordinates of the input vertices, subtracted from them
+    pub fn centroid(&self) -> &Vector {
+        &self.0.centroid
+    }
+}
+
+impl RigidBody for RigidTriMesh {
+    fn mass(&self) -> f64 {
+        self.0.mass
+    }
+    fn inertia_tensor(&self) -> Matrix {
+        self.0.inertia.clone()
+    }
+    fn shape(&self) -> Shape {
+        Shape::TriMesh {
+            vertices: self.0.vertices.clone(),
+            triangles: self.0.triangles.clone(),
+        }
+    }
+    fn material(&self) -> Option<Material> {
+        self.0.material
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::approx_eq::{assert_approx_eq, ApproxEq};
+    use crate::world::polyhedron::tests::cuboid;
+    use crate::world::rigid_box::RigidBox;
+
+    #[test]
+    fn test_concave_mesh() {
+        // two unit cubes side by side, sharing no faces, form a 2 x 1 x 1 slab
+        let (mut vertices, mut triangles) =
+            cuboid(&Vector::new(0.0, 0.0, 0.0), &Vector::new(1.0, 1.0, 1.0));
+        let (right, right_triangles) =
+            cuboid(&Vector::new(1.0, 0.0, 0.0), &Vector::new(2.0, 1.0, 1.0));
+        triangles.extend(
+            right_triangles
+                .iter()
+                .map(|t| t.map(|i| i + vertices.len())),
+        );
+        vertices.extend(right);
+        let mesh = RigidTriMesh::new(&vertices, &triangles, 1.0);
+        let slab = RigidBox::new(2.0, 1.0, 1.0, 1.0);
+        assert_approx_eq!(mesh.mass(), slab.mass());
+        assert_approx_eq!(mesh.inertia_tensor(), slab.inertia_tensor());
+        assert_approx_eq!(mesh.centroid(), &Vector::new(1.0, 0.5, 0.5));
+    }
+
+    #[test]
+    fn test_l_shaped_mesh() {
+        // an L of three unit cubes in the xy plane: (0,0), (1,0) and (0,1)
+        let mut vertices = Vec::new();
+        let mut triangles = Vec::new();
+        for (x, y) in [(0.0, 0.0), (1.0, 0.0), (0.0, 1.0)] {
+            let (v, t) = cuboid(&Vector::new(x, y, 0.0), &Vector::new(x + 1.0, y + 1.0, 1.0));
+            triangles.extend(t.iter().map(|t| t.map(|i| i + vertices.len())));
+            vertices.extend(v);
+        }
+        let mesh = RigidTriMesh::new(&vertices, &triangles, 1.0);
+        assert_approx_eq!(mesh.mass(), 3.0);
+        assert_approx_eq!(mesh.centroid(), &Vector::new(5.0 / 6.0, 5.0 / 6.0, 0.5));
+        // Ixy = -sum m x y over the cube centers relative to the centroid
+        let (near, far) = (-1.0 / 3.0, 2.0 / 3.0);
+        let ixy = -(near * near + far * near + near * far);
+        assert_approx_eq!(mesh.inertia_tensor().column(1).x, ixy);
+    }
+}
@@ -0,0 +1,11 @@
+use super::handle::BodyHandle;
+use super::query::Collider;
+
+// A body starting or ceasing to overlap a sensor. Sensors detect bodies which
+// are not sensors themselves, without touching them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SensorEvent {
+    Enter { sensor: Collider, body: BodyHandle },
+    // also reported for bodies removed while inside
+    Exit { sensor: Collider, body: BodyHandle },
+}
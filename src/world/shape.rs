@@ -0,0 +1,49 @@
+use crate::math::quaternion::Quaternion;
+use crate::math::vector::Vector;
+
+// Geometry of a body in body coordinates, primitives centered on the origin.
+// Round shapes have their axis along z.
+#[derive(Debug, Clone)]
+pub enum Shape {
+    Box {
+        half_extents: Vector,
+    },
+    Sphere {
+        radius: f64,
+    },
+    // cylinder of height 2 * half_height capped by two hemispheres
+    Capsule {
+        radius: f64,
+        half_height: f64,
+    },
+    Cylinder {
+        radius: f64,
+        half_height: f64,
+    },
+    // base at z = -height / 4, apex at z = 3 * height / 4
+    Cone {
+        radius: f64,
+        height: f64,
+    },
+    // closed triangle meshes, triangles wound counter-clockwise seen from outside
+    ConvexHull {
+        vertices: Vec<Vector>,
+        triangles: Vec<[usize; 3]>,
+    },
+    // collides and is hit by rays as the convex hull of its vertices
+    TriMesh {
+        vertices: Vec<Vector>,
+        triangles: Vec<[usize; 3]>,
+    },
+    Compound {
+        children: Vec<ShapeChild>,
+    },
+}
+
+// Part of a compound shape, posed in the compound's body coordinates
+#[derive(Debug, Clone)]
+pub struct ShapeChild {
+    pub position: Vector,
+    pub orientation: Quaternion,
+    pub shape: Shape,
+}
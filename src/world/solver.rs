@@ -0,0 +1,329 @@
+use super::collision::ContactManifold;
+use super::material::{ContactMaterial, Material};
+use crate::math::matrix::Matrix;
+use crate::math::vector::Vector;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PositionCorrection {
+    // feeds part of the penetration back into the velocity constraints; resting
+    // bodies keep the velocity that holds them up against the next step's gravity
+    Baumgarte,
+    // removes penetration with pseudo velocities that are discarded after the step
+    SplitImpulse,
+}
+
+#[derive(Debug, Clone)]
+pub struct SolverConfig {
+    pub iterations: usize,
+    // of contacts between colliders without a material
+    pub friction: f64,
+    pub restitution: f64,
+    // closing speeds below this do not bounce
+    pub restitution_threshold: f64,
+    pub position_correction: PositionCorrection,
+    // fraction of the penetration corrected per step
+    pub correction_factor: f64,
+    // penetration allowed without correction
+    pub slop: f64,
+}
+
+impl SolverConfig {
+    // Material standing in for colliders which have none
+    pub(crate) fn material(&self) -> Material {
+        Material::new(self.friction, self.restitution, 1.0)
+    }
+}
+
+impl Default for SolverConfig {
+    fn default() -> Self {
+        Self {
+            iterations: 10,
+            friction: 0.5,
+            restitution: 0.2,
+            restitution_threshold: 1.0,
+            position_correction: PositionCorrection::SplitImpulse,
+            correction_factor: 0.2,
+            slop: 0.005,
+        }
+    }
+}
+
+pub(crate) struct SolverBody {
+    pub(crate) x: Vector,
+    pub(crate) v: Vector,
+    pub(crate) w: Vector,
+    pub(crate) inv_mass: f64,
+    // in world coordinates
+    pub(crate) inv_inertia: Matrix,
+    // accumulated linear and angular impulse
+    pub(crate) dp: Vector,
+    pub(crate) dl: Vector,
+    // split impulse pseudo velocities
+    pub(crate) pv: Vector,
+    pub(crate) pw: Vector,
+}
+
+impl SolverBody {
+    pub(crate) fn new(
+        x: &Vector,
+        v: &Vector,
+        w: &Vector,
+        inv_mass: f64,
+        inv_inertia: Matrix,
+    ) -> Self {
+        Self {
+            x: x.clone(),
+            v: v.clone(),
+            w: w.clone(),
+            inv_mass,
+            inv_inertia,
+            dp: Vector::zero(),
+            dl: Vector::zero(),
+            pv: Vector::zero(),
+            pw: Vector::zero(),
+        }
+    }
+    // Immovable body standing in for static geometry
+    pub(crate) fn fixed() -> Self {
+        Self::new(
+            &Vector::zero(),
+            &Vector::zero(),
+            &Vector::zero(),
+            0.0,
+            Matrix::new([0.0; 9]),
+        )
+    }
+}
+
+// Contact manifold between solver bodies `a` and `b`
+pub(crate) struct ContactPair {
+    pub(crate) a: usize,
+    pub(crate) b: usize,
+    pub(crate) manifold: ContactManifold,
+    pub(crate) material: ContactMaterial,
+}
+
+// Contact points sliding slower than this are held by static friction
+const STICKING_SPEED: f64 = 0.01;
+
+// One scalar velocity constraint, the relative velocity being
+// lin . (v_b - v_a) + ang_b . w_b - ang_a . w_a
+pub(crate) struct Row {
+    a: usize,
+    b: usize,
+    lin: Vector,
+    ang_a: Vector,
+    ang_b: Vector,
+    eff_mass: f64,
+    // target relative velocity
+    pub(crate) bias: f64,
+    pub(crate) lower: f64,
+    pub(crate) upper: f64,
+    // compliance of soft constraints, which give way in proportion to their impulse
+    softness: f64,
+    // bounds are +-coefficient times the impulse of this row
+    friction: Option<(usize, f64)>,
+    impulse: f64,
+}
+
+impl Row {
+    pub(crate) fn new(
+        bodies: &[SolverBody],
+        a: usize,
+        b: usize,
+        lin: Vector,
+        ang_a: Vector,
+        ang_b: Vector,
+    ) -> Self {
+        let (ba, bb) = (&bodies[a], &bodies[b]);
+        let k = (ba.inv_mass + bb.inv_mass) * lin.dot(&lin)
+            + (&ba.inv_inertia * &ang_a).dot(&ang_a)
+            + (&bb.inv_inertia * &ang_b).dot(&ang_b);
+        Self {
+            a,
+            b,
+            lin,
+            ang_a,
+            ang_b,
+            eff_mass: if k > 0.0 { 1.0 / k } else { 0.0 },
+            bias: 0.0,
+            lower: f64::NEG_INFINITY,
+            upper: f64::INFINITY,
+            friction: None,
+            softness: 0.0,
+            impulse: 0.0,
+        }
+    }
+    // Turns the row into a spring of stiffness `k` and damping `c` towards the
+    // position where `error` is zero
+    pub(crate) fn soften(&mut self, k: f64, c: f64, error: f64, dt: f64) {
+        let softness = 1.0 / (dt * (c + dt * k));
+        self.softness = softness;
+        self.bias = -error * dt * k * softness;
+        if self.eff_mass > 0.0 {
+            self.eff_mass = 1.0 / (1.0 / self.eff_mass + softness);
+        }
+    }
+    // Row along `direction` for the point `point` shared by both bodies
+    pub(crate) fn at_point(
+        bodies: &[SolverBody],
+        a: usize,
+        b: usize,
+        point: &Vector,
+        direction: &Vector,
+    ) -> Self {
+        let ang_a = (point - &bodies[a].x).cross(direction);
+        let ang_b = (point - &bodies[b].x).cross(direction);
+        Self::new(bodies, a, b, direction.clone(), ang_a, ang_b)
+    }
+    pub(crate) fn velocity(&self, bodies: &[SolverBody], pseudo: bool) -> f64 {
+        let (a, b) = (&bodies[self.a], &bodies[self.b]);
+        if pseudo {
+            self.lin.dot(&(&b.pv - &a.pv)) + self.ang_b.dot(&b.pw) - self.ang_a.dot(&a.pw)
+        } else {
+            self.lin.dot(&(&b.v - &a.v)) + self.ang_b.dot(&b.w) - self.ang_a.dot(&a.w)
+        }
+    }
+    fn apply(&self, bodies: &mut [SolverBody], impulse: f64, pseudo: bool) {
+        let lin = &self.lin * impulse;
+        let ang_a = &self.ang_a * impulse;
+        let ang_b = &self.ang_b * impulse;
+        let a = &mut bodies[self.a];
+        let dv = &lin * a.inv_mass;
+        let dw = &a.inv_inertia * &ang_a;
+        if pseudo {
+            a.pv = &a.pv - &dv;
+            a.pw = &a.pw - &dw;
+        } else {
+            a.v = &a.v - &dv;
+            a.w = &a.w - &dw;
+            a.dp = &a.dp - &lin;
+            a.dl = &a.dl - &ang_a;
+        }
+        let b = &mut bodies[self.b];
+        let dv = &lin * b.inv_mass;
+        let dw = &b.inv_inertia * &ang_b;
+        if pseudo {
+            b.pv = &b.pv + &dv;
+            b.pw = &b.pw + &dw;
+        } else {
+            b.v = &b.v + &dv;
+            b.w = &b.w + &dw;
+            b.dp = &b.dp + &lin;
+            b.dl = &b.dl + &ang_b;
+        }
+    }
+}
+
+// Projected Gauss-Seidel over `rows`
+pub(crate) fn solve_rows(
+    rows: &mut [Row],
+    bodies: &mut [SolverBody],
+    iterations: usize,
+    pseudo: bool,
+) {
+    for _ in 0..iterations {
+        for i in 0..rows.len() {
+            if let Some((normal, coefficient)) = rows[i].friction {
+                let limit = coefficient * rows[normal].impulse;
+                rows[i].lower = -limit;
+                rows[i].upper = limit;
+            }
+            let row = &mut rows[i];
+            let delta = row.eff_mass
+                * (row.bias - row.velocity(bodies, pseudo) - row.softness * row.impulse);
+            let impulse = (row.impulse + delta).clamp(row.lower, row.upper);
+            let delta = impulse - row.impulse;
+            row.impulse = impulse;
+            row.apply(bodies, delta, pseudo);
+        }
+    }
+}
+
+// Resolves `contacts`, together with the joint rows `rows`, by sequential impulses
+// on the velocities of `bodies`. Returns the impulse applied to body `b` at each
+// contact point of each pair; rolling friction is left out, being a torque.
+pub(crate) fn solve_contacts(
+    bodies: &mut [SolverBody],
+    contacts: &[ContactPair],
+    mut rows: Vec<Row>,
+    config: &SolverConfig,
+    dt: f64,
+) -> Vec<Vec<Vector>> {
+    let joint_rows = rows.len();
+    let mut position_rows = Vec::new();
+    let mut rolling_rows = Vec::new();
+    for pair in contacts {
+        let n = &pair.manifold.normal;
+        let (t, u) = n.orthonormal_basis();
+        let material = &pair.material;
+        for c in &pair.manifold.points {
+            let mut row = Row::at_point(bodies, pair.a, pair.b, &c.point, n);
+            row.lower = 0.0;
+            let vn = row.velocity(bodies, false);
+            if vn < -config.restitution_threshold {
+                row.bias = -material.restitution * vn;
+            }
+            let correction = config.correction_factor / dt * (c.depth - config.slop).max(0.0);
+            match config.position_correction {
+                PositionCorrection::Baumgarte => row.bias = row.bias.max(correction),
+                PositionCorrection::SplitImpulse => {
+                    let mut position_row = Row::at_point(bodies, pair.a, pair.b, &c.point, n);
+                    position_row.lower = 0.0;
+                    position_row.bias = correction;
+                    position_rows.push(position_row);
+                }
+            }
+            let normal = rows.len();
+            rows.push(row);
+            let friction_rows = [&t, &u]
+                .map(|direction| Row::at_point(bodies, pair.a, pair.b, &c.point, direction));
+            let slip = friction_rows
+                .iter()
+                .map(|row| row.velocity(bodies, false))
+                .fold(0.0, f64::hypot);
+            let friction = if slip < STICKING_SPEED {
+                material.static_friction
+            } else {
+                material.dynamic_friction
+            };
+            for mut row in friction_rows {
+                row.friction = Some((normal, friction));
+                rows.push(row);
+            }
+            if material.rolling_friction > 0.0 {
+                // resists the relative rotation about the tangents
+                for direction in [&t, &u] {
+                    let mut row = Row::new(
+                        bodies,
+                        pair.a,
+                        pair.b,
+                        Vector::zero(),
+                        direction.clone(),
+                        direction.clone(),
+                    );
+                    row.friction = Some((normal, material.rolling_friction));
+                    rolling_rows.push(row);
+                }
+            }
+        }
+    }
+    // after the point rows, which are read back below in threes
+    rows.extend(rolling_rows);
+    solve_rows(&mut rows, bodies, config.iterations, false);
+    solve_rows(&mut position_rows, bodies, config.iterations, true);
+    // each contact point has a normal and two friction rows
+    let mut point_rows = rows[joint_rows..].chunks(3);
+    contacts
+        .iter()
+        .map(|pair| {
+            (&mut point_rows)
+                .take(pair.manifold.points.len())
+                .map(|rows| {
+                    rows.iter()
+                        .fold(Vector::zero(), |sum, row| sum + &(&row.lin * row.impulse))
+                })
+                .collect()
+        })
+        .collect()
+}
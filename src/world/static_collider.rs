@@ -0,0 +1,179 @@
+use super::aabb::Aabb;
+use super::collision::cast::{cast_shape, Impact};
+use super::collision::plane::plane_shape_posed;
+use super::collision::ray::{ray_plane, ray_shape, RayIntersection};
+use super::collision::support::{Posed, SupportMap};
+use super::collision::{posed_parts, shape_shape, ContactManifold};
+use super::collision_groups::CollisionGroups;
+use super::material::Material;
+use super::shape::Shape;
+use crate::math::matrix::Matrix;
+use crate::math::quaternion::Quaternion;
+use crate::math::vector::Vector;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StaticHandle(pub(crate) usize);
+
+pub(crate) enum StaticGeometry {
+    // all points p with normal . p = offset
+    Plane {
+        normal: Vector,
+        offset: f64,
+    },
+    Box {
+        half_extents: Vector,
+        x: Vector,
+        r: Matrix,
+    },
+}
+
+// Immovable geometry the bodies in a `World` collide against
+pub struct StaticCollider {
+    pub(crate) geometry: StaticGeometry,
+    pub(crate) groups: CollisionGroups,
+    pub(crate) sensor: bool,
+    // the solver's friction and restitution apply without one
+    pub(crate) material: Option<Material>,
+}
+
+impl StaticCollider {
+    // Infinite plane whose solid side lies opposite `normal`
+    pub fn plane(normal: &Vector, offset: f64) -> Self {
+        Self {
+            geometry: StaticGeometry::Plane {
+                normal: normal.normalize(),
+                offset,
+            },
+            groups: CollisionGroups::ALL,
+            sensor: false,
+            material: None,
+        }
+    }
+    pub fn cuboid(x: f64, y: f64, z: f64, position: &Vector, q: &Quaternion) -> Self {
+        Self {
+            geometry: StaticGeometry::Box {
+                half_extents: Vector::new(0.5 * x, 0.5 * y, 0.5 * z),
+                x: position.clone(),
+                r: q.normalize().to_rotation_matrix(),
+            },
+            groups: CollisionGroups::ALL,
+            sensor: false,
+            material: None,
+        }
+    }
+    pub fn with_groups(mut self, groups: CollisionGroups) -> Self {
+        self.groups = groups;
+        self
+    }
+    // Detects bodies instead of colliding with them
+    pub fn as_sensor(mut self) -> Self {
+        self.sensor = true;
+        self
+    }
+    pub fn with_material(mut self, material: Material) -> Self {
+        self.material = Some(material);
+        self
+    }
+    pub(crate) fn cast_ray(
+        &self,
+        origin: &Vector,
+        direction: &Vector,
+        max_t: f64,
+    ) -> Option<RayIntersection> {
+        match &self.geometry {
+            StaticGeometry::Plane { normal, offset } => {
+                ray_plane(origin, direction, max_t, normal, *offset)
+            }
+            StaticGeometry::Box { half_extents, x, r } => {
+                let shape = Shape::Box {
+                    half_extents: half_extents.clone(),
+                };
+                ray_shape(origin, direction, max_t, x, r, &shape)
+            }
+        }
+    }
+    // Contacts with `shape` at `x` with orientation `r`, the collider being A
+    pub(crate) fn contacts(&self, x: &Vector, r: &Matrix, shape: &Shape) -> Vec<ContactManifold> {
+        match &self.geometry {
+            StaticGeometry::Plane { normal, offset } => {
+                plane_shape_posed(normal, *offset, x, r, shape)
+                    .into_iter()
+                    .collect()
+            }
+            StaticGeometry::Box {
+                half_extents,
+                x: static_x,
+                r: static_r,
+            } => {
+                let cuboid = Shape::Box {
+                    half_extents: half_extents.clone(),
+                };
+                posed_parts(x, r, shape)
+                    .into_iter()
+                    .filter_map(|(x, r, part)| {
+                        shape_shape(static_x, static_r, &cuboid, &x, &r, part)
+                    })
+                    .collect()
+            }
+        }
+    }
+    pub(crate) fn cast_shape(
+        &self,
+        x: &Vector,
+        r: &Matrix,
+        shape: &Shape,
+        direction: &Vector,
+        max_t: f64,
+    ) -> Option<Impact> {
+        match &self.geometry {
+            StaticGeometry::Plane { normal, offset } => {
+                // the point of the shape deepest below the plane touches first
+                let lowest = Posed { x, r, shape }.support(&-normal);
+                let height = normal.dot(&lowest) - offset;
+                let approach = -normal.dot(direction);
+                let t = if height <= 0.0 {
+                    0.0
+                } else if approach > 0.0 && height / approach <= max_t {
+                    height / approach
+                } else {
+                    return None;
+                };
+                Some(Impact {
+                    t,
+                    normal: normal.clone(),
+                    point: lowest + &(direction * t),
+                })
+            }
+            StaticGeometry::Box {
+                half_extents,
+                x: static_x,
+                r: static_r,
+            } => {
+                let cuboid = Shape::Box {
+                    half_extents: half_extents.clone(),
+                };
+                cast_shape(x, r, shape, direction, max_t, static_x, static_r, &cuboid)
+            }
+        }
+    }
+    // Planes are tested exactly, boxes by their bounds
+    pub(crate) fn overlaps_aabb(&self, aabb: &Aabb) -> bool {
+        match &self.geometry {
+            StaticGeometry::Plane { normal, offset } => {
+                let corner = |n: f64, min: f64, max: f64| if n > 0.0 { min } else { max };
+                let lowest = Vector::new(
+                    corner(normal.x, aabb.min.x, aabb.max.x),
+                    corner(normal.y, aabb.min.y, aabb.max.y),
+                    corner(normal.z, aabb.min.z, aabb.max.z),
+                );
+                normal.dot(&lowest) <= *offset
+            }
+            StaticGeometry::Box { half_extents, x, r } => {
+                let cuboid = Shape::Box {
+                    half_extents: half_extents.clone(),
+                };
+                Aabb::of_shape(x, r, &cuboid).overlaps(aabb)
+            }
+        }
+    }
+}
@@ -1,176 +1,2729 @@
+use crate::math::approx_eq::ApproxEq;
 use crate::math::matrix::Matrix;
 use crate::math::quaternion::Quaternion;
 use crate::math::vector::Vector;
+use std::collections::HashSet;
 
+use super::aabb::Aabb;
+use super::body_type::BodyType;
+use super::broad_phase::sweep_and_prune::SweepAndPrune;
+use super::broad_phase::BroadPhase;
+use super::collision::cast::{cast_shape, Impact};
+use super::collision::ray::{ray_shape, RayIntersection};
+use super::collision::{posed_parts, shape_shape, ContactManifold};
+use super::collision_groups::CollisionGroups;
+use super::contact_event::{ContactEvent, ContactReport};
+use super::contact_hooks::ContactHooks;
+use super::handle::{BodyHandle, HandleMap};
+use super::integrator::rk4::Rk4;
+use super::integrator::Integrator;
+use super::island::{SleepConfig, UnionFind};
+use super::joint::{AttachedJoint, Joint, JointHandle, JointMotor, Pose};
+use super::material::{ContactMaterial, Material};
+use super::query::{Collider, QueryFilter, RayHit, ShapeHit};
 use super::rigid_body::RigidBody;
+use super::rigid_body_state::RigidBodyState;
+use super::sensor::SensorEvent;
+use super::shape::{Shape, ShapeChild};
+use super::solver::{solve_contacts, ContactPair, PositionCorrection, SolverBody, SolverConfig};
+use super::static_collider::{StaticCollider, StaticHandle};
 
-struct RigidBodyState {
-    // position
-    x: Vector,
-    // orientation
-    q: Quaternion,
-    // linear momentum
-    p: Vector,
-    // angular momentum
-    l: Vector,
-    // orientation matrix
-    r: Matrix,
-    // linear velocity
-    v: Vector,
-    // angular velocity
-    w: Vector,
-}
+// Indices of two objects
+type Pair = (usize, usize);
 
-impl RigidBodyState {
-    fn new(
-        x: &Vector,
-        q: &Quaternion,
-        p: &Vector,
-        l: &Vector,
-        inv_mass: f64,
-        inv_inertia: &Matrix,
-    ) -> Self {
-        let q = q.normalize(); // always necessary?
-        let r = q.to_rotation_matrix();
-        let v = p * inv_mass;
-        let w = &r * (inv_inertia * (&r.transpose() * l));
-        Self {
-            x: x.clone(),
-            q,
-            p: p.clone(),
-            l: l.clone(),
-            r,
-            v,
-            w,
-        }
-    }
+// How far continuous collision detection lets a body into what it hits
+const CCD_PENETRATION: f64 = 0.01;
+
+// Direction of a query, None for zero and non-finite vectors
+fn unit_direction(direction: &Vector) -> Option<Vector> {
+    let length = direction.magnitude();
+    (length > 0.0 && length.is_finite()).then(|| direction / length)
 }
 
-struct WorldObject {
-    body_id: usize,
+pub struct WorldObject {
+    handle: BodyHandle,
     inv_mass: f64,
     inv_inertia: Matrix,
+    shape: Shape,
+    // `shape` placed in the simulated frame, which sits at the center of mass
+    // along the principal axes
+    collider: Shape,
+    // the frame the body was defined in, relative to the simulated frame
+    frame_x: Vector,
+    frame_q: Quaternion,
     state: RigidBodyState,
+    // pose before the last step, for render interpolation
+    prev_x: Vector,
+    prev_q: Quaternion,
+    // external force and torque accumulated for the next step
+    force: Vector,
+    torque: Vector,
+    body_type: BodyType,
+    // pose a kinematic body is to reach in the next step
+    target: Option<(Vector, Quaternion)>,
+    groups: CollisionGroups,
+    // sensors detect other bodies instead of colliding with them
+    sensor: bool,
+    // swept against other colliders so that it cannot pass through them
+    ccd: bool,
+    // the solver's friction and restitution apply without one
+    material: Option<Material>,
+    asleep: bool,
+    // how long the body has been slow enough to sleep
+    sleep_time: f64,
+    // moved by hand since the broad phase last saw it
+    teleported: bool,
 }
 
-const TORQUE: Vector = Vector::new(0.0, 0.0, 0.0);
-
 impl WorldObject {
-    fn step(&mut self, _t: f64, dt: f64, force: &Vector) {
-        let halfdt = 0.5 * dt;
-        let thirddt = dt / 3.0;
-        let sixthdt = dt / 6.0;
-        let s0 = &self.state;
-
-        // a1 = G(t, s0), b1 = s0 + (dt / 2) * a1
-        let a1dxdt = &s0.v;
-        let a1dqdt = &Quaternion::new(&s0.w * 0.5, 0.0) * &s0.q;
-        let a1dpdt = force;
-        let a1dldt = &TORQUE;
-
-        let x = &s0.x + &(a1dxdt * halfdt);
-        let q = &s0.q + &(&a1dqdt * halfdt);
-        let p = &s0.p + &(a1dpdt * halfdt);
-        let l = &s0.l + &(a1dldt * halfdt);
-        let b1 = RigidBodyState::new(&x, &q, &p, &l, self.inv_mass, &self.inv_inertia);
-
-        // a2 = G(t + dt / 2, b1), b2 = s0 + (dt / 2) * a2
-        let a2dxdt = &b1.v;
-        let a2dqdt = &Quaternion::new(&b1.w * 0.5, 0.0) * &b1.q;
-        let a2dpdt = force;
-        let a2dldt = &TORQUE;
-
-        let x = &s0.x + &(a2dxdt * halfdt);
-        let q = &s0.q + &(&a2dqdt * halfdt);
-        let p = &s0.p + &(a2dpdt * halfdt);
-        let l = &s0.l + &(a2dldt * halfdt);
-        let b2 = RigidBodyState::new(&x, &q, &p, &l, self.inv_mass, &self.inv_inertia);
-
-        // a3 = G(t + dt / 2, b2), b3 = s0 + dt * a3
-        let a3dxdt = &b2.v;
-        let a3dqdt = &Quaternion::new(&b2.w * 0.5, 0.0) * &b2.q;
-        let a3dpdt = force;
-        let a3dldt = &TORQUE;
-
-        let x = &s0.x + &(a3dxdt * dt);
-        let q = &s0.q + &(&a3dqdt * dt);
-        let p = &s0.p + &(a3dpdt * dt);
-        let l = &s0.l + &(a3dldt * dt);
-        let b3 = RigidBodyState::new(&x, &q, &p, &l, self.inv_mass, &self.inv_inertia);
-
-        // a4 = G(t + dt, b4), s1 = s0 + (dt / 6) * (a1 + 2 * a2 + 2 * a3 + a4)
-        let a4dxdt = &b3.v;
-        let a4dqdt = &Quaternion::new(&b3.w * 0.5, 0.0) * &b3.q;
-        let a4dpdt = force;
-        let a4dldt = &TORQUE;
-
-        let x = &s0.x
-            + &(a1dxdt * sixthdt)
-            + &(a2dxdt * thirddt)
-            + &(a3dxdt * thirddt)
-            + &(a4dxdt * sixthdt);
-        let q = &s0.q
-            + &(&a1dqdt * sixthdt)
-            + &(&a2dqdt * thirddt)
-            + &(&a3dqdt * thirddt)
-            + &(&a4dqdt * sixthdt);
-        let p = &s0.p
-            + &(a1dpdt * sixthdt)
-            + &(a2dpdt * thirddt)
-            + &(a3dpdt * thirddt)
-            + &(a4dpdt * sixthdt);
-        let l = &s0.l
-            + &(a1dldt * sixthdt)
-            + &(a2dldt * thirddt)
-            + &(a3dldt * thirddt)
-            + &(a4dldt * sixthdt);
-        let s1 = RigidBodyState::new(&x, &q, &p, &l, self.inv_mass, &self.inv_inertia);
-
-        self.state = s1;
+    pub fn handle(&self) -> BodyHandle {
+        self.handle
+    }
+    // Pose of the coordinates the body and its shape were defined in
+    pub fn position(&self) -> Vector {
+        &self.state.x + &(&self.state.r * &self.frame_x)
+    }
+    pub fn orientation(&self) -> Quaternion {
+        (&self.state.q * &self.frame_q).normalize()
+    }
+    pub fn center_of_mass(&self) -> &Vector {
+        &self.state.x
+    }
+    // Pose of the body frame for the simulated frame at `x` with orientation `q`
+    fn to_body_frame(&self, x: &Vector, q: &Quaternion) -> (Vector, Quaternion) {
+        (
+            x + &(&q.to_rotation_matrix() * &self.frame_x),
+            (q * &self.frame_q).normalize(),
+        )
+    }
+    fn to_simulated_frame(&self, x: &Vector, q: &Quaternion) -> (Vector, Quaternion) {
+        let q = (q * &self.frame_q.conj()).normalize();
+        (x - &(&q.to_rotation_matrix() * &self.frame_x), q)
+    }
+    pub fn linear_velocity(&self) -> &Vector {
+        &self.state.v
+    }
+    pub fn angular_velocity(&self) -> &Vector {
+        &self.state.w
+    }
+    pub fn mass(&self) -> f64 {
+        1.0 / self.inv_mass
+    }
+    // In body coordinates
+    pub fn inertia_tensor(&self) -> Matrix {
+        let r = self.frame_q.to_rotation_matrix();
+        &(&r.transpose() * &self.inv_inertia.inverse().unwrap()) * &r
+    }
+    pub fn shape(&self) -> &Shape {
+        &self.shape
+    }
+    // Changing the state by hand wakes the body
+    fn set_state(&mut self, x: &Vector, q: &Quaternion, p: &Vector, l: &Vector) {
+        self.state = RigidBodyState::new(x, q, p, l, self.inv_mass, &self.inv_inertia);
+        self.wake();
+    }
+    pub fn is_asleep(&self) -> bool {
+        self.asleep
+    }
+    pub fn collision_groups(&self) -> CollisionGroups {
+        self.groups
+    }
+    pub fn set_collision_groups(&mut self, groups: CollisionGroups) {
+        self.groups = groups;
+        self.wake();
+    }
+    pub fn is_sensor(&self) -> bool {
+        self.sensor
+    }
+    pub fn set_sensor(&mut self, sensor: bool) {
+        self.sensor = sensor;
+        self.wake();
+    }
+    pub fn material(&self) -> Option<&Material> {
+        self.material.as_ref()
+    }
+    pub fn set_material(&mut self, material: Option<Material>) {
+        self.material = material;
+        self.wake();
+    }
+    pub fn is_ccd_enabled(&self) -> bool {
+        self.ccd
+    }
+    // Continuous collision detection, for small fast bodies which would otherwise
+    // pass through thin obstacles between two steps
+    pub fn set_ccd_enabled(&mut self, ccd: bool) {
+        self.ccd = ccd;
+        self.wake();
+    }
+    fn wake(&mut self) {
+        self.asleep = false;
+        self.sleep_time = 0.0;
+    }
+    fn sleep(&mut self) {
+        let s = &self.state;
+        let zero = Vector::zero();
+        self.state =
+            RigidBodyState::new(&s.x, &s.q, &zero, &zero, self.inv_mass, &self.inv_inertia);
+        self.asleep = true;
+    }
+    // Dynamic and awake, so moved by the solver
+    fn is_active(&self) -> bool {
+        self.is_dynamic() && !self.asleep
+    }
+    fn is_moving(&self) -> bool {
+        self.state.v.magnitude() > 0.0 || self.state.w.magnitude() > 0.0
+    }
+    // Pose a fraction `alpha` of the way from the previous to the current step
+    pub fn interpolated_pose(&self, alpha: f64) -> (Vector, Quaternion) {
+        self.to_body_frame(
+            &self.prev_x.lerp(&self.state.x, alpha),
+            &self.prev_q.slerp(&self.state.q, alpha),
+        )
+    }
+    // Teleports the body, without interpolating from the old position
+    pub fn set_position(&mut self, x: &Vector) {
+        let s = &self.state;
+        let x = x - &(&s.r * &self.frame_x);
+        let (q, p, l) = (s.q.clone(), s.p.clone(), s.l.clone());
+        self.set_state(&x, &q, &p, &l);
+        self.prev_x = x;
+        self.teleported = true;
+    }
+    // Turns the body about its origin. Keeps the angular momentum, so the angular
+    // velocity follows the new orientation.
+    pub fn set_orientation(&mut self, q: &Quaternion) {
+        let (x, q) = self.to_simulated_frame(&self.position(), q);
+        let (p, l) = (self.state.p.clone(), self.state.l.clone());
+        self.set_state(&x, &q, &p, &l);
+        self.prev_x = self.state.x.clone();
+        self.prev_q = self.state.q.clone();
+        self.teleported = true;
+    }
+    // Static bodies keep still whatever velocity they are given
+    pub fn set_linear_velocity(&mut self, v: &Vector) {
+        if self.body_type == BodyType::Static {
+            return;
+        }
+        let s = &self.state;
+        let (x, q, l) = (s.x.clone(), s.q.clone(), s.l.clone());
+        self.set_state(&x, &q, &(v / self.inv_mass), &l);
+    }
+    pub fn set_angular_velocity(&mut self, w: &Vector) {
+        if self.body_type == BodyType::Static {
+            return;
+        }
+        let s = &self.state;
+        let inertia = s.inv_inertia_world(&self.inv_inertia).inverse().unwrap();
+        let (x, q, p) = (s.x.clone(), s.q.clone(), s.p.clone());
+        self.set_state(&x, &q, &p, &(&inertia * w));
+    }
+    pub fn body_type(&self) -> BodyType {
+        self.body_type
+    }
+    // A body made static stops where it is
+    pub fn set_body_type(&mut self, body_type: BodyType) {
+        if body_type == BodyType::Static {
+            self.set_linear_velocity(&Vector::zero());
+            self.set_angular_velocity(&Vector::zero());
+        }
+        self.body_type = body_type;
+    }
+    pub fn is_dynamic(&self) -> bool {
+        self.body_type == BodyType::Dynamic
+    }
+    // Gives a kinematic body the velocities that take it to the pose in the next
+    // step, which it keeps afterwards unless given another target or velocity
+    pub fn set_target_pose(&mut self, x: &Vector, q: &Quaternion) {
+        self.target = Some(self.to_simulated_frame(x, &q.normalize()));
+    }
+    // Moves a kinematic body along its velocities
+    fn move_kinematic(&mut self, dt: f64) {
+        if let Some((x, q)) = self.target.take() {
+            let mut turn = &q * &self.state.q.conj();
+            if turn.w < 0.0 {
+                turn = &turn * -1.0;
+            }
+            let sin = turn.v.magnitude();
+            let w = if sin > 0.0 {
+                &turn.v * (2.0 * sin.atan2(turn.w) / (sin * dt))
+            } else {
+                Vector::zero()
+            };
+            self.set_linear_velocity(&(&(&x - &self.state.x) / dt));
+            self.set_angular_velocity(&w);
+        }
+        let (v, w) = (self.state.v.clone(), self.state.w.clone());
+        let x = &self.state.x + &(&v * dt);
+        let speed = w.magnitude();
+        let q = if speed > 0.0 {
+            (&Quaternion::from_rotation(&(&w / speed), speed * dt) * &self.state.q).normalize()
+        } else {
+            self.state.q.clone()
+        };
+        let p = self.state.p.clone();
+        self.set_state(&x, &q, &p, &Vector::zero());
+        self.set_angular_velocity(&w);
+    }
+    fn pose(&self) -> Pose<'_> {
+        Pose {
+            x: &self.state.x,
+            q: &self.state.q,
+            r: &self.state.r,
+        }
+    }
+    // World space bounds of the shape
+    pub fn aabb(&self) -> Aabb {
+        Aabb::of_shape(&self.state.x, &self.state.r, &self.collider)
+    }
+    fn static_contact(&self, collider: &StaticCollider) -> Vec<ContactManifold> {
+        collider.contacts(&self.state.x, &self.state.r, &self.collider)
+    }
+    // One manifold per pair of touching parts
+    fn contact(&self, other: &WorldObject) -> Vec<ContactManifold> {
+        let parts = posed_parts(&self.state.x, &self.state.r, &self.collider);
+        let other_parts = posed_parts(&other.state.x, &other.state.r, &other.collider);
+        let mut manifolds = Vec::new();
+        for (x, r, part) in &parts {
+            for (other_x, other_r, other_part) in &other_parts {
+                manifolds.extend(shape_shape(x, r, part, other_x, other_r, other_part));
+            }
+        }
+        manifolds
     }
 }
 
 pub struct World {
     objects: Vec<WorldObject>,
+    handles: HandleMap<BodyHandle>,
+    statics: Vec<StaticCollider>,
+    joints: Vec<(JointHandle, AttachedJoint)>,
+    joint_handles: HandleMap<JointHandle>,
     gravity: Vector,
+    integrator: Box<dyn Integrator>,
+    broad_phase: Box<dyn BroadPhase>,
+    // the bounds the broad phase was last synced with
+    bounds: Vec<Aabb>,
+    solver_config: SolverConfig,
+    sleep_config: SleepConfig,
+    hooks: Option<Box<dyn ContactHooks>>,
+    // sensors and the bodies inside them, in order of entry
+    sensor_overlaps: Vec<(Collider, BodyHandle)>,
+    sensor_events: Vec<SensorEvent>,
+    // pairs of colliders touching after the last step
+    touching: Vec<(Collider, Collider)>,
+    contact_events: Vec<ContactEvent>,
+    // fixed timestep state for `advance`
+    time: f64,
+    fixed_dt: f64,
+    max_substeps: usize,
+    accumulator: f64,
 }
 
 impl World {
+    // `gravity` is an acceleration, so bodies of any mass fall alike
     pub fn new(gravity: Vector) -> Self {
         Self {
             objects: Vec::new(),
+            handles: HandleMap::new(),
+            statics: Vec::new(),
+            joints: Vec::new(),
+            joint_handles: HandleMap::new(),
             gravity,
+            integrator: Box::new(Rk4),
+            broad_phase: Box::new(SweepAndPrune::new()),
+            bounds: Vec::new(),
+            solver_config: SolverConfig::default(),
+            sleep_config: SleepConfig::default(),
+            hooks: None,
+            sensor_overlaps: Vec::new(),
+            sensor_events: Vec::new(),
+            touching: Vec::new(),
+            contact_events: Vec::new(),
+            time: 0.0,
+            fixed_dt: 1.0 / 60.0,
+            max_substeps: 8,
+            accumulator: 0.0,
         }
     }
+    // Adds a body with linear velocity `v` and angular momentum `l`
     pub fn add<B: RigidBody>(
         &mut self,
-        body_id: usize,
         body: &B,
         x: &Vector,
         q: &Quaternion,
         v: &Vector,
         l: &Vector,
-    ) {
+    ) -> BodyHandle {
         let inv_mass = 1.0 / body.mass();
-        let inv_inertia = body.inertia_tensor().inverse().unwrap();
-        let state = RigidBodyState::new(x, q, v, l, inv_mass, &inv_inertia);
+        // simulate the principal axes at the center of mass, so the inertia is diagonal
+        let (moments, axes) = body.inertia_tensor().symmetric_eigen();
+        let inv_inertia = Matrix::diagonal(1.0 / moments.x, 1.0 / moments.y, 1.0 / moments.z);
+        let center = body.center_of_mass();
+        let principal = axes.to_quaternion();
+        let frame_x = -&(&axes.transpose() * &center);
+        let frame_q = principal.conj();
+        let shape = body.shape();
+        let collider = if center.approx_eq(&Vector::zero()) && axes.approx_eq(&Matrix::identity()) {
+            shape.clone()
+        } else {
+            Shape::Compound {
+                children: vec![ShapeChild {
+                    position: frame_x.clone(),
+                    orientation: frame_q.clone(),
+                    shape: shape.clone(),
+                }],
+            }
+        };
+        let x = x + &(&q.to_rotation_matrix() * &center);
+        let q = (q * &principal).normalize();
+        let state = RigidBodyState::new(&x, &q, &(v * body.mass()), l, inv_mass, &inv_inertia);
+        let handle = self.handles.insert(self.objects.len());
         let object = WorldObject {
-            body_id,
+            handle,
+            prev_x: state.x.clone(),
+            prev_q: state.q.clone(),
             state,
             inv_mass,
             inv_inertia,
+            shape,
+            collider,
+            frame_x,
+            frame_q,
+            force: Vector::zero(),
+            torque: Vector::zero(),
+            body_type: BodyType::Dynamic,
+            target: None,
+            groups: CollisionGroups::ALL,
+            sensor: false,
+            ccd: false,
+            material: body.material(),
+            asleep: false,
+            sleep_time: 0.0,
+            teleported: false,
         };
         self.objects.push(object);
+        self.sync_broad_phase();
+        handle
+    }
+    // Returns false if the body was already removed
+    // Also removes the joints attached to the body, and wakes the bodies around it
+    pub fn remove(&mut self, handle: BodyHandle) -> bool {
+        match self.handles.remove(handle) {
+            Some(index) => {
+                let aabb = self.objects[index].aabb();
+                for o in &mut self.objects {
+                    if o.aabb().overlaps(&aabb) {
+                        o.wake();
+                    }
+                }
+                let attached: Vec<JointHandle> = self
+                    .joints
+                    .iter()
+                    .filter(|(_, j)| j.b == handle || j.a == Some(handle))
+                    .map(|&(joint, _)| joint)
+                    .collect();
+                for joint in attached {
+                    self.remove_joint(joint);
+                }
+                self.objects.swap_remove(index);
+                if let Some(moved) = self.objects.get(index) {
+                    self.handles.relocate(moved.handle, index);
+                }
+                self.sync_broad_phase();
+                true
+            }
+            None => false,
+        }
+    }
+    pub fn contains(&self, handle: BodyHandle) -> bool {
+        self.handles.get(handle).is_some()
+    }
+    pub fn get(&self, handle: BodyHandle) -> Option<&WorldObject> {
+        self.handles.get(handle).map(|index| &self.objects[index])
+    }
+    pub fn get_mut(&mut self, handle: BodyHandle) -> Option<&mut WorldObject> {
+        self.handles
+            .get(handle)
+            .map(|index| &mut self.objects[index])
+    }
+    pub fn add_static(&mut self, collider: StaticCollider) -> StaticHandle {
+        self.statics.push(collider);
+        StaticHandle(self.statics.len() - 1)
+    }
+    // Joins body `b` to body `a`, or to the world when `a` is None. Returns None if
+    // either body has been removed.
+    pub fn add_joint(
+        &mut self,
+        a: Option<BodyHandle>,
+        b: BodyHandle,
+        joint: &Joint,
+    ) -> Option<JointHandle> {
+        let pose_a = match a {
+            Some(a) => self.get(a)?.pose(),
+            None => Pose::world(),
+        };
+        let attached = AttachedJoint::new(joint, a, &pose_a, b, &self.get(b)?.pose());
+        for handle in [a, Some(b)].into_iter().flatten() {
+            self.wake(handle);
+        }
+        let handle = self.joint_handles.insert(self.joints.len());
+        self.joints.push((handle, attached));
+        Some(handle)
+    }
+    // Returns false if the joint was already removed
+    pub fn remove_joint(&mut self, handle: JointHandle) -> bool {
+        match self.joint_handles.remove(handle) {
+            Some(index) => {
+                self.joints.swap_remove(index);
+                if let Some(&(moved, _)) = self.joints.get(index) {
+                    self.joint_handles.relocate(moved, index);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+    // Replaces the motor of a hinge or slider; false if the joint was removed
+    pub fn set_joint_motor(&mut self, handle: JointHandle, motor: Option<JointMotor>) -> bool {
+        match self.joint_handles.get(handle) {
+            Some(index) => {
+                let joint = &mut self.joints[index].1;
+                joint.motor = motor;
+                let bodies = [joint.a, Some(joint.b)];
+                for handle in bodies.into_iter().flatten() {
+                    self.wake(handle);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+    // Angle of a hinge or offset of a slider from its pose when added
+    pub fn joint_position(&self, handle: JointHandle) -> Option<f64> {
+        let joint = &self.joints[self.joint_handles.get(handle)?].1;
+        let pose_a = match joint.a {
+            Some(a) => self.get(a)?.pose(),
+            None => Pose::world(),
+        };
+        joint.position(&pose_a, &self.get(joint.b)?.pose())
+    }
+    pub fn set_integrator<I: Integrator + 'static>(&mut self, integrator: I) {
+        self.integrator = Box::new(integrator);
+    }
+    pub fn set_broad_phase<B: BroadPhase + 'static>(&mut self, broad_phase: B) {
+        self.broad_phase = Box::new(broad_phase);
+        self.sync_broad_phase();
+    }
+    // Sensor events of the last step, or of all the steps of the last `advance`
+    pub fn sensor_events(&self) -> &[SensorEvent] {
+        &self.sensor_events
+    }
+    // Contact events of every step since the last call; they queue up until drained
+    pub fn drain_contact_events(&mut self) -> std::vec::Drain<'_, ContactEvent> {
+        self.contact_events.drain(..)
+    }
+    pub fn set_contact_hooks<H: ContactHooks + 'static>(&mut self, hooks: H) {
+        self.hooks = Some(Box::new(hooks));
+    }
+    pub fn clear_contact_hooks(&mut self) {
+        self.hooks = None;
+    }
+    pub fn solver_config(&self) -> &SolverConfig {
+        &self.solver_config
+    }
+    pub fn set_solver_config(&mut self, config: SolverConfig) {
+        self.solver_config = config;
+    }
+    pub fn sleep_config(&self) -> &SleepConfig {
+        &self.sleep_config
+    }
+    pub fn set_sleep_config(&mut self, config: SleepConfig) {
+        self.sleep_config = config;
+    }
+    pub fn is_asleep(&self, handle: BodyHandle) -> Option<bool> {
+        self.get(handle).map(WorldObject::is_asleep)
+    }
+    pub fn wake(&mut self, handle: BodyHandle) {
+        if let Some(o) = self.get_mut(handle) {
+            o.wake();
+        }
+    }
+    // Forces, torques and impulses only act on dynamic bodies
+    // Force acting at the center of mass during the next step
+    pub fn apply_force(&mut self, handle: BodyHandle, force: &Vector) {
+        if let Some(o) = self.get_mut(handle).filter(|o| o.is_dynamic()) {
+            o.force = &o.force + force;
+            o.wake();
+        }
+    }
+    // Force acting at the world position `point` during the next step
+    pub fn apply_force_at_point(&mut self, handle: BodyHandle, force: &Vector, point: &Vector) {
+        if let Some(o) = self.get_mut(handle).filter(|o| o.is_dynamic()) {
+            o.force = &o.force + force;
+            o.torque = &o.torque + &(point - &o.state.x).cross(force);
+            o.wake();
+        }
+    }
+    // Torque acting during the next step
+    pub fn apply_torque(&mut self, handle: BodyHandle, torque: &Vector) {
+        if let Some(o) = self.get_mut(handle).filter(|o| o.is_dynamic()) {
+            o.torque = &o.torque + torque;
+            o.wake();
+        }
+    }
+    // Instantaneous change of momentum at the world position `point`
+    pub fn apply_impulse(&mut self, handle: BodyHandle, impulse: &Vector, point: &Vector) {
+        if let Some(o) = self.get_mut(handle).filter(|o| o.is_dynamic()) {
+            o.state
+                .apply_impulse(impulse, point, o.inv_mass, &o.inv_inertia);
+            o.wake();
+        }
+    }
+    // Panics unless `dt` is positive and finite
+    pub fn set_fixed_timestep(&mut self, dt: f64) {
+        assert!(dt > 0.0 && dt.is_finite(), "invalid fixed timestep {}", dt);
+        self.fixed_dt = dt;
+    }
+    // Steps dropped beyond this are lost, so slow frames slow the simulation down
+    // instead of making it fall further behind. Panics if zero.
+    pub fn set_max_substeps(&mut self, max_substeps: usize) {
+        assert!(max_substeps > 0, "at least one substep is needed");
+        self.max_substeps = max_substeps;
+    }
+    pub fn time(&self) -> f64 {
+        self.time
+    }
+    // Runs as many fixed steps as fit in the elapsed real time, returning how many ran.
+    // Panics unless `real_dt` is zero or positive and finite.
+    pub fn advance(&mut self, real_dt: f64) -> usize {
+        assert!(
+            real_dt >= 0.0 && real_dt.is_finite(),
+            "invalid elapsed time {}",
+            real_dt
+        );
+        self.accumulator += real_dt;
+        let mut substeps = 0;
+        let mut sensor_events = Vec::new();
+        while self.accumulator >= self.fixed_dt {
+            if substeps == self.max_substeps {
+                self.accumulator %= self.fixed_dt;
+                break;
+            }
+            self.time = self.step(self.time, self.fixed_dt);
+            sensor_events.append(&mut self.sensor_events);
+            self.accumulator -= self.fixed_dt;
+            substeps += 1;
+        }
+        self.sensor_events = sensor_events;
+        substeps
+    }
+    // How far the leftover time in the accumulator reaches into the next step
+    pub fn interpolation_alpha(&self) -> f64 {
+        self.accumulator / self.fixed_dt
+    }
+    pub fn interpolated_pose(&self, handle: BodyHandle) -> Option<(Vector, Quaternion)> {
+        let alpha = self.interpolation_alpha();
+        self.get(handle).map(|o| o.interpolated_pose(alpha))
+    }
+    // Closest collider hit by the ray from `origin` along `direction` within
+    // `max_distance`. Rays starting inside a solid hit it at distance zero, and
+    // rays without a direction hit nothing.
+    pub fn cast_ray(
+        &self,
+        origin: &Vector,
+        direction: &Vector,
+        max_distance: f64,
+        filter: &QueryFilter,
+    ) -> Option<RayHit> {
+        self.cast_ray_all(origin, direction, max_distance, filter)
+            .into_iter()
+            .next()
+    }
+    // Every collider hit by the ray, nearest first
+    pub fn cast_ray_all(
+        &self,
+        origin: &Vector,
+        direction: &Vector,
+        max_distance: f64,
+        filter: &QueryFilter,
+    ) -> Vec<RayHit> {
+        let Some(direction) = unit_direction(direction) else {
+            return Vec::new();
+        };
+        let hit = |collider, intersection: RayIntersection| RayHit {
+            collider,
+            point: origin + &(&direction * intersection.t),
+            normal: intersection.normal,
+            distance: intersection.t,
+        };
+        let mut hits = Vec::new();
+        for (i, collider) in self.statics.iter().enumerate() {
+            if filter.accepts_static(collider) {
+                if let Some(intersection) = collider.cast_ray(origin, &direction, max_distance) {
+                    hits.push(hit(Collider::Static(StaticHandle(i)), intersection));
+                }
+            }
+        }
+        let candidates = self
+            .broad_phase
+            .cast_ray(&self.bounds, origin, &direction, max_distance);
+        for i in self.with_teleported(candidates) {
+            let o = &self.objects[i];
+            if !filter.accepts_body(o) {
+                continue;
+            }
+            if let Some(intersection) = ray_shape(
+                origin,
+                &direction,
+                max_distance,
+                &o.state.x,
+                &o.state.r,
+                &o.collider,
+            ) {
+                hits.push(hit(Collider::Body(o.handle), intersection));
+            }
+        }
+        hits.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+        hits
+    }
+    // First collider hit by `shape` swept from `x` with orientation `q` along
+    // `direction` for up to `max_distance`. Shapes overlapping at the start hit at
+    // distance zero, and sweeps without a direction hit nothing.
+    pub fn cast_shape(
+        &self,
+        shape: &Shape,
+        x: &Vector,
+        q: &Quaternion,
+        direction: &Vector,
+        max_distance: f64,
+        filter: &QueryFilter,
+    ) -> Option<ShapeHit> {
+        let r = q.normalize().to_rotation_matrix();
+        let direction = unit_direction(direction)?;
+        self.shape_hits(shape, x, &r, &direction, max_distance, filter)
+            .into_iter()
+            .min_by(|a, b| a.distance.total_cmp(&b.distance))
+    }
+    // Every collider hit by the sweep, in no particular order
+    fn shape_hits(
+        &self,
+        shape: &Shape,
+        x: &Vector,
+        r: &Matrix,
+        direction: &Vector,
+        max_distance: f64,
+        filter: &QueryFilter,
+    ) -> Vec<ShapeHit> {
+        let hit = |collider, impact: Impact| ShapeHit {
+            collider,
+            point: impact.point,
+            normal: impact.normal,
+            distance: impact.t,
+        };
+        let mut hits = Vec::new();
+        for (i, collider) in self.statics.iter().enumerate() {
+            if filter.accepts_static(collider) {
+                if let Some(impact) = collider.cast_shape(x, r, shape, direction, max_distance) {
+                    hits.push(hit(Collider::Static(StaticHandle(i)), impact));
+                }
+            }
+        }
+        let start = Aabb::of_shape(x, r, shape);
+        let end = Aabb::of_shape(&(x + &(direction * max_distance)), r, shape);
+        let candidates = self
+            .broad_phase
+            .overlapping(&self.bounds, &start.merge(&end));
+        for i in self.with_teleported(candidates) {
+            let o = &self.objects[i];
+            if !filter.accepts_body(o) {
+                continue;
+            }
+            let impact = cast_shape(
+                x,
+                r,
+                shape,
+                direction,
+                max_distance,
+                &o.state.x,
+                &o.state.r,
+                &o.collider,
+            );
+            if let Some(impact) = impact {
+                hits.push(hit(Collider::Body(o.handle), impact));
+            }
+        }
+        hits
+    }
+    // Colliders touching or overlapping `shape` placed at `x` with orientation `q`
+    pub fn intersect_shape(
+        &self,
+        shape: &Shape,
+        x: &Vector,
+        q: &Quaternion,
+        filter: &QueryFilter,
+    ) -> Vec<Collider> {
+        let r = q.normalize().to_rotation_matrix();
+        let mut found = Vec::new();
+        for (i, collider) in self.statics.iter().enumerate() {
+            if filter.accepts_static(collider) && !collider.contacts(x, &r, shape).is_empty() {
+                found.push(Collider::Static(StaticHandle(i)));
+            }
+        }
+        let parts = posed_parts(x, &r, shape);
+        let candidates = self
+            .broad_phase
+            .overlapping(&self.bounds, &Aabb::of_shape(x, &r, shape));
+        for i in self.with_teleported(candidates) {
+            let o = &self.objects[i];
+            if !filter.accepts_body(o) {
+                continue;
+            }
+            let other_parts = posed_parts(&o.state.x, &o.state.r, &o.collider);
+            let touching = parts.iter().any(|(x, r, part)| {
+                other_parts
+                    .iter()
+                    .any(|(ox, or, other)| shape_shape(x, r, part, ox, or, other).is_some())
+            });
+            if touching {
+                found.push(Collider::Body(o.handle));
+            }
+        }
+        found
+    }
+    // Colliders whose bounds overlap `aabb`, planes tested exactly
+    pub fn intersect_aabb(&self, aabb: &Aabb, filter: &QueryFilter) -> Vec<Collider> {
+        let mut found = Vec::new();
+        for (i, collider) in self.statics.iter().enumerate() {
+            if filter.accepts_static(collider) && collider.overlaps_aabb(aabb) {
+                found.push(Collider::Static(StaticHandle(i)));
+            }
+        }
+        let candidates = self.broad_phase.overlapping(&self.bounds, aabb);
+        for i in self.with_teleported(candidates) {
+            let o = &self.objects[i];
+            if filter.accepts_body(o) && o.aabb().overlaps(aabb) {
+                found.push(Collider::Body(o.handle));
+            }
+        }
+        found
     }
     pub fn step(&mut self, t: f64, dt: f64) -> f64 {
         for o in &mut self.objects {
-            o.step(t, dt, &self.gravity);
+            o.prev_x = o.state.x.clone();
+            o.prev_q = o.state.q.clone();
+            let force =
+                &(&self.gravity / o.inv_mass) + &std::mem::replace(&mut o.force, Vector::zero());
+            let torque = std::mem::replace(&mut o.torque, Vector::zero());
+            match o.body_type {
+                BodyType::Static => continue,
+                BodyType::Kinematic => {
+                    o.move_kinematic(dt);
+                    continue;
+                }
+                BodyType::Dynamic if o.asleep => continue,
+                BodyType::Dynamic => (),
+            }
+            o.state = self.integrator.integrate(
+                &o.state,
+                o.inv_mass,
+                &o.inv_inertia,
+                &force,
+                &torque,
+                dt,
+            );
+        }
+        if self.objects.iter().any(|o| o.ccd && o.is_active()) {
+            // the sweeps look for the other bodies where they are now
+            self.sync_broad_phase();
+            self.sweep_ccd_bodies();
+        }
+        let (pairs, sensor_pairs) = self.find_pairs();
+        self.detect_sensors(&sensor_pairs);
+        let islands = self.wake_islands(&pairs);
+        let (colliders, contacts): (Vec<_>, Vec<_>) = self.find_contacts(pairs).into_iter().unzip();
+        if !contacts.is_empty() || !self.joints.is_empty() {
+            let impulses = self.solve(&contacts, dt);
+            self.report_contacts(&colliders, &contacts, &impulses);
+        } else {
+            self.report_contacts(&[], &[], &[]);
         }
+        if self.sleep_config.enabled {
+            self.update_sleep(&islands, dt);
+        }
+        self.sync_broad_phase();
         t + dt
     }
-    pub fn for_each_object<C: FnMut(usize, &Vector, &Quaternion)>(&self, mut callback: C) {
+    // Pulls bodies with continuous collision detection back to where their motion
+    // in this step first hits another collider, letting them in just deep enough
+    // for the contact to be found. Colliders already touching at the start of the
+    // step are left to the contacts.
+    fn sweep_ccd_bodies(&mut self) {
+        let jointed = self.jointed();
+        for i in 0..self.objects.len() {
+            let o = &self.objects[i];
+            let motion = &o.state.x - &o.prev_x;
+            let length = motion.magnitude();
+            if !o.ccd || !o.is_active() || o.sensor || length <= CCD_PENETRATION {
+                continue;
+            }
+            let mut filter = QueryFilter::new()
+                .with_groups(o.groups)
+                .without_sensors()
+                .excluding(o.handle);
+            for &(a, b) in &jointed {
+                if a == i || b == i {
+                    filter = filter.excluding(self.objects[a + b - i].handle);
+                }
+            }
+            let body = Collider::Body(o.handle);
+            let (shape, start) = (o.collider.clone(), o.prev_x.clone());
+            let r = o.prev_q.normalize().to_rotation_matrix();
+            let direction = &motion / length;
+            let hits = self.shape_hits(&shape, &start, &r, &direction, length, &filter);
+            let allowed = |other: Collider| {
+                // the same order as the pairs of the broad phase
+                let (a, b) = match other {
+                    Collider::Body(handle) if self.handles.get(handle) > Some(i) => (body, other),
+                    _ => (other, body),
+                };
+                self.hooks
+                    .as_ref()
+                    .is_none_or(|hooks| hooks.filter_pair(a, b))
+            };
+            let first = hits
+                .iter()
+                .filter(|hit| hit.distance > 0.0 && allowed(hit.collider))
+                .map(|hit| hit.distance)
+                .min_by(f64::total_cmp);
+            if let Some(distance) = first {
+                let travel = (distance + CCD_PENETRATION).min(length);
+                self.objects[i].state.x = start + &(&direction * travel);
+            }
+        }
+    }
+    fn aabbs(&self) -> Vec<Aabb> {
+        self.objects.iter().map(WorldObject::aabb).collect()
+    }
+    // Gives the broad phase the current bounds of the bodies, so that the queries
+    // can use it without changing it
+    fn sync_broad_phase(&mut self) {
+        self.bounds = self.aabbs();
+        self.broad_phase.sync(&self.bounds);
+        for o in &mut self.objects {
+            o.teleported = false;
+        }
+    }
+    // Broad phase candidates of a query, in order, together with the bodies moved
+    // by hand since the last sync, which it may have missed
+    fn with_teleported(&self, mut candidates: Vec<usize>) -> Vec<usize> {
+        candidates.extend((0..self.objects.len()).filter(|&i| self.objects[i].teleported));
+        candidates.sort_unstable();
+        candidates.dedup();
+        candidates
+    }
+    // Pairs of bodies with overlapping bounds allowed to touch: those with at least
+    // one dynamic body to collide, and those with a sensor
+    fn find_pairs(&mut self) -> (Vec<Pair>, Vec<Pair>) {
+        self.bounds = self.aabbs();
+        let mut pairs = self.broad_phase.update(&self.bounds);
+        for o in &mut self.objects {
+            o.teleported = false;
+        }
+        // a fixed order keeps the solver deterministic whatever the broad phase
+        pairs.sort_unstable_by_key(|&(a, b)| (b, a));
+        pairs.retain(|&(a, b)| {
+            let (a, b) = (&self.objects[a], &self.objects[b]);
+            self.may_touch(
+                Collider::Body(a.handle),
+                &a.groups,
+                Collider::Body(b.handle),
+                &b.groups,
+            )
+        });
+        let (sensor_pairs, mut pairs): (Vec<_>, Vec<_>) = pairs
+            .into_iter()
+            .partition(|&(a, b)| self.objects[a].sensor || self.objects[b].sensor);
+        pairs.retain(|&(a, b)| self.objects[a].is_dynamic() || self.objects[b].is_dynamic());
+        (pairs, sensor_pairs)
+    }
+    // Replaces the sensor events with the changes in what overlaps the sensors
+    fn detect_sensors(&mut self, pairs: &[(usize, usize)]) {
+        let mut overlaps = Vec::new();
+        for (i, collider) in self.statics.iter().enumerate() {
+            if !collider.sensor {
+                continue;
+            }
+            let sensor = Collider::Static(StaticHandle(i));
+            for o in &self.objects {
+                if !o.sensor
+                    && self.may_touch(
+                        sensor,
+                        &collider.groups,
+                        Collider::Body(o.handle),
+                        &o.groups,
+                    )
+                    && collider.overlaps_aabb(&o.aabb())
+                    && !collider
+                        .contacts(&o.state.x, &o.state.r, &o.collider)
+                        .is_empty()
+                {
+                    overlaps.push((sensor, o.handle));
+                }
+            }
+        }
+        for &(a, b) in pairs {
+            let (a, b) = (&self.objects[a], &self.objects[b]);
+            // sensors do not detect each other
+            let (sensor, body) = match (a.sensor, b.sensor) {
+                (true, false) => (a, b),
+                (false, true) => (b, a),
+                _ => continue,
+            };
+            if !sensor.contact(body).is_empty() {
+                overlaps.push((Collider::Body(sensor.handle), body.handle));
+            }
+        }
+        let previous: HashSet<_> = self.sensor_overlaps.iter().copied().collect();
+        let current: HashSet<_> = overlaps.iter().copied().collect();
+        self.sensor_events = self
+            .sensor_overlaps
+            .iter()
+            .filter(|overlap| !current.contains(overlap))
+            .map(|&(sensor, body)| SensorEvent::Exit { sensor, body })
+            .chain(
+                overlaps
+                    .iter()
+                    .filter(|overlap| !previous.contains(overlap))
+                    .map(|&(sensor, body)| SensorEvent::Enter { sensor, body }),
+            )
+            .collect();
+        // keep the order of entry
+        self.sensor_overlaps
+            .retain(|overlap| current.contains(overlap));
+        self.sensor_overlaps.extend(
+            overlaps
+                .into_iter()
+                .filter(|overlap| !previous.contains(overlap)),
+        );
+    }
+    // Whether the collision groups and the user's hooks let a pair touch
+    fn may_touch(
+        &self,
+        a: Collider,
+        groups_a: &CollisionGroups,
+        b: Collider,
+        groups_b: &CollisionGroups,
+    ) -> bool {
+        groups_a.interacts_with(groups_b)
+            && self
+                .hooks
+                .as_ref()
+                .is_none_or(|hooks| hooks.filter_pair(a, b))
+    }
+    // Object indices of the dynamic bodies joined by joints and the pairs
+    fn jointed(&self) -> Vec<(usize, usize)> {
+        self.joints
+            .iter()
+            .filter_map(|(_, joint)| {
+                let a = self.handles.get(joint.a?)?;
+                let b = self.handles.get(joint.b)?;
+                Some((a.min(b), a.max(b)))
+            })
+            .collect()
+    }
+    // Groups the dynamic bodies into islands joined by possible contacts and joints,
+    // and wakes every island with an awake body or touching a moving kinematic body.
+    // Returns the island of each object.
+    fn wake_islands(&mut self, pairs: &[(usize, usize)]) -> Vec<usize> {
+        let mut islands = UnionFind::new(self.objects.len());
+        let jointed = self.jointed();
+        for &(a, b) in pairs.iter().chain(&jointed) {
+            if self.objects[a].is_dynamic() && self.objects[b].is_dynamic() {
+                islands.union(a, b);
+            }
+        }
+        let mut awake = vec![false; self.objects.len()];
+        for (i, o) in self.objects.iter().enumerate() {
+            if o.is_active() {
+                awake[islands.find(i)] = true;
+            }
+        }
+        for &(a, b) in pairs.iter().chain(&jointed) {
+            for (mover, other) in [(a, b), (b, a)] {
+                let mover = &self.objects[mover];
+                if mover.body_type == BodyType::Kinematic && mover.is_moving() {
+                    awake[islands.find(other)] = true;
+                }
+            }
+        }
+        let roots: Vec<usize> = (0..self.objects.len()).map(|i| islands.find(i)).collect();
+        for (o, root) in self.objects.iter_mut().zip(&roots) {
+            if o.asleep && awake[*root] {
+                o.wake();
+            }
+        }
+        roots
+    }
+    // Islands whose bodies have all been slow for long enough fall asleep together
+    fn update_sleep(&mut self, islands: &[usize], dt: f64) {
+        let config = &self.sleep_config;
+        let mut slowest = vec![f64::INFINITY; self.objects.len()];
+        for (o, &island) in self.objects.iter_mut().zip(islands) {
+            if !o.is_active() {
+                continue;
+            }
+            if o.state.v.magnitude() < config.linear_threshold
+                && o.state.w.magnitude() < config.angular_threshold
+            {
+                o.sleep_time += dt;
+            } else {
+                o.sleep_time = 0.0;
+            }
+            slowest[island] = slowest[island].min(o.sleep_time);
+        }
+        for (o, &island) in self.objects.iter_mut().zip(islands) {
+            if o.is_active() && slowest[island] >= config.time_to_sleep {
+                o.sleep();
+            }
+        }
+    }
+    // Contact pairs for the solver with the colliders they came from
+    fn find_contacts(&self, mut pairs: Vec<Pair>) -> Vec<((Collider, Collider), ContactPair)> {
+        // static geometry is represented by one fixed solver body after the objects
+        let fixed = self.objects.len();
+        let mut contacts = Vec::new();
+        for (b, object) in self.objects.iter().enumerate() {
+            if !object.is_active() || object.sensor {
+                continue;
+            }
+            for (i, collider) in self.statics.iter().enumerate() {
+                let a = Collider::Static(StaticHandle(i));
+                let body = Collider::Body(object.handle);
+                if collider.sensor || !self.may_touch(a, &collider.groups, body, &object.groups) {
+                    continue;
+                }
+                let material = self.contact_material(collider.material, object.material);
+                for manifold in object.static_contact(collider) {
+                    contacts.extend(self.modify_contact((a, body), fixed, b, manifold, material));
+                }
+            }
+        }
+        // jointed bodies do not collide with each other
+        let jointed: HashSet<(usize, usize)> = self.jointed().into_iter().collect();
+        pairs.retain(|&(a, b)| {
+            (self.objects[a].is_active() || self.objects[b].is_active())
+                && !jointed.contains(&(a, b))
+        });
+        for (a, b) in pairs {
+            let (body_a, body_b) = (&self.objects[a], &self.objects[b]);
+            let colliders = (Collider::Body(body_a.handle), Collider::Body(body_b.handle));
+            let material = self.contact_material(body_a.material, body_b.material);
+            for manifold in body_a.contact(body_b) {
+                contacts.extend(self.modify_contact(colliders, a, b, manifold, material));
+            }
+        }
+        contacts
+    }
+    // Coefficients of a contact between colliders of the given materials
+    fn contact_material(&self, a: Option<Material>, b: Option<Material>) -> ContactMaterial {
+        let fallback = self.solver_config.material();
+        a.unwrap_or(fallback).combine(&b.unwrap_or(fallback))
+    }
+    // Passes a manifold between solver bodies `a` and `b` through the user's hooks
+    fn modify_contact(
+        &self,
+        colliders: (Collider, Collider),
+        a: usize,
+        b: usize,
+        mut manifold: ContactManifold,
+        material: ContactMaterial,
+    ) -> Option<((Collider, Collider), ContactPair)> {
+        if let Some(hooks) = &self.hooks {
+            hooks.modify_contact(colliders.0, colliders.1, &mut manifold);
+        }
+        (!manifold.points.is_empty()).then_some((
+            colliders,
+            ContactPair {
+                a,
+                b,
+                manifold,
+                material,
+            },
+        ))
+    }
+    // Queues the events for the pairs touching in this step, and for those which
+    // stopped touching since the last
+    fn report_contacts(
+        &mut self,
+        colliders: &[(Collider, Collider)],
+        contacts: &[ContactPair],
+        impulses: &[Vec<Vector>],
+    ) {
+        // one report per pair of colliders, gathering the manifolds of their parts
+        let mut reports: Vec<(ContactReport, f64)> = Vec::new();
+        for ((&(a, b), pair), impulses) in colliders.iter().zip(contacts).zip(impulses) {
+            let normal = &pair.manifold.normal;
+            let index = match reports.iter().position(|(r, _)| r.a == a && r.b == b) {
+                Some(index) => index,
+                None => {
+                    let report = ContactReport {
+                        a,
+                        b,
+                        impulse: Vector::zero(),
+                        point: pair.manifold.points[0].point.clone(),
+                        normal: normal.clone(),
+                    };
+                    reports.push((report, f64::NEG_INFINITY));
+                    reports.len() - 1
+                }
+            };
+            let (report, strongest) = &mut reports[index];
+            for (c, impulse) in pair.manifold.points.iter().zip(impulses) {
+                report.impulse = &report.impulse + impulse;
+                if impulse.dot(normal) > *strongest {
+                    *strongest = impulse.dot(normal);
+                    report.point = c.point.clone();
+                    report.normal = normal.clone();
+                }
+            }
+        }
+        let touching: HashSet<(Collider, Collider)> =
+            reports.iter().map(|(r, _)| (r.a, r.b)).collect();
+        let previous: HashSet<(Collider, Collider)> = self.touching.iter().copied().collect();
+        // pairs of sleeping or removed bodies were not looked at
+        let looked_at = |collider: &Collider| match collider {
+            Collider::Body(handle) => self.get(*handle).is_none_or(WorldObject::is_active),
+            Collider::Static(_) => false,
+        };
+        let (ended, kept): (Vec<_>, Vec<_>) = self
+            .touching
+            .iter()
+            .filter(|pair| !touching.contains(pair))
+            .partition(|(a, b)| looked_at(a) || looked_at(b));
+        self.contact_events
+            .extend(ended.iter().map(|&(a, b)| ContactEvent::Ended { a, b }));
+        self.touching = kept;
+        for (report, _) in reports {
+            self.touching.push((report.a, report.b));
+            self.contact_events
+                .push(if previous.contains(&(report.a, report.b)) {
+                    ContactEvent::Persisted(report)
+                } else {
+                    ContactEvent::Started(report)
+                });
+        }
+    }
+    fn solve(&mut self, contacts: &[ContactPair], dt: f64) -> Vec<Vec<Vector>> {
+        let mut bodies: Vec<SolverBody> = self
+            .objects
+            .iter()
+            .map(|o| {
+                if o.is_active() {
+                    SolverBody::new(
+                        &o.state.x,
+                        &o.state.v,
+                        &o.state.w,
+                        o.inv_mass,
+                        o.state.inv_inertia_world(&o.inv_inertia),
+                    )
+                } else {
+                    // moves at its own velocity whatever it touches
+                    SolverBody::new(
+                        &o.state.x,
+                        &o.state.v,
+                        &o.state.w,
+                        0.0,
+                        Matrix::new([0.0; 9]),
+                    )
+                }
+            })
+            .collect();
+        bodies.push(SolverBody::fixed());
+        let fixed = self.objects.len();
+        let correction_factor = self.solver_config.correction_factor;
+        let mut rows = Vec::new();
+        for (_, joint) in &self.joints {
+            let a = joint.a.map_or(Some(fixed), |a| self.handles.get(a));
+            if let (Some(ia), Some(ib)) = (a, self.handles.get(joint.b)) {
+                let pose_a = if ia == fixed {
+                    Pose::world()
+                } else {
+                    self.objects[ia].pose()
+                };
+                let pose_b = self.objects[ib].pose();
+                rows.extend(joint.rows(
+                    &bodies,
+                    (ia, &pose_a),
+                    (ib, &pose_b),
+                    correction_factor,
+                    dt,
+                ));
+            }
+        }
+        let impulses = solve_contacts(&mut bodies, contacts, rows, &self.solver_config, dt);
+        let split = self.solver_config.position_correction == PositionCorrection::SplitImpulse;
+        for (o, body) in self.objects.iter_mut().zip(&bodies) {
+            if !o.is_active() {
+                continue;
+            }
+            let s = &o.state;
+            let p = &s.p + &body.dp;
+            let l = &s.l + &body.dl;
+            let (x, q) = if split {
+                (
+                    &s.x + &(&body.pv * dt),
+                    &s.q + &(&(&Quaternion::new(&body.pw * 0.5, 0.0) * &s.q) * dt),
+                )
+            } else {
+                (s.x.clone(), s.q.clone())
+            };
+            o.state = RigidBodyState::new(&x, &q, &p, &l, o.inv_mass, &o.inv_inertia);
+        }
+        impulses
+    }
+    pub fn for_each_object<C: FnMut(BodyHandle, &Vector, &Quaternion)>(&self, mut callback: C) {
+        for o in &self.objects {
+            callback(o.handle, &o.position(), &o.orientation());
+        }
+    }
+    // Like `for_each_object`, with poses interpolated for rendering after `advance`
+    pub fn for_each_interpolated<C: FnMut(BodyHandle, &Vector, &Quaternion)>(
+        &self,
+        mut callback: C,
+    ) {
+        let alpha = self.interpolation_alpha();
         for o in &self.objects {
-            callback(o.body_id, &o.state.x, &o.state.q);
+            let (x, q) = o.interpolated_pose(alpha);
+            callback(o.handle, &x, &q);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::approx_eq::{assert_approx_eq, ApproxEq};
+    use crate::world::broad_phase::dynamic_aabb_tree::DynamicAabbTree;
+    use crate::world::compound_body::CompoundBody;
+    use crate::world::integrator::euler::SemiImplicitEuler;
+    use crate::world::material::CombineRule;
+    use crate::world::rigid_box::RigidBox;
+    use crate::world::rigid_capsule::RigidCapsule;
+    use crate::world::rigid_cylinder::RigidCylinder;
+    use crate::world::rigid_sphere::RigidSphere;
+
+    #[test]
+    fn test_dropped_box_comes_to_rest_on_plane() {
+        let mut world = World::new(Vector::new(0.0, 0.0, -10.0));
+        world.set_sleep_config(SleepConfig {
+            enabled: false,
+            ..SleepConfig::default()
+        });
+        world.add_static(StaticCollider::plane(&Vector::new(0.0, 0.0, 1.0), 0.0));
+        world.add(
+            &RigidBox::new(1.0, 2.0, 1.0, 1.0),
+            &Vector::new(0.0, 0.0, 3.0),
+            &Quaternion::coords(0.0, 0.0, 0.0, 1.0),
+            &Vector::zero(),
+            &Vector::zero(),
+        );
+        let mut t = 0.0;
+        for _ in 0..300 {
+            t = world.step(t, 0.01);
+        }
+        let state = &world.objects[0].state;
+        assert!((state.x.z - 0.5).abs() < 0.02, "z = {}", state.x.z);
+        assert!(state.v.magnitude() < 0.05, "v = {:?}", state.v);
+    }
+
+    #[test]
+    fn test_box_lands_on_static_box() {
+        let mut world = World::new(Vector::new(0.0, 0.0, -10.0));
+        world.add_static(StaticCollider::cuboid(
+            4.0,
+            4.0,
+            1.0,
+            &Vector::new(0.0, 0.0, 1.5),
+            &Quaternion::coords(0.0, 0.0, 0.0, 1.0),
+        ));
+        world.add(
+            &RigidBox::new(1.0, 1.0, 1.0, 1.0),
+            &Vector::new(0.0, 0.0, 4.0),
+            &Quaternion::coords(0.0, 0.0, 0.0, 1.0),
+            &Vector::zero(),
+            &Vector::zero(),
+        );
+        let mut t = 0.0;
+        for _ in 0..300 {
+            t = world.step(t, 0.01);
+        }
+        let state = &world.objects[0].state;
+        assert!((state.x.z - 2.5).abs() < 0.02, "z = {}", state.x.z);
+    }
+
+    #[test]
+    fn test_box_lands_on_compound_table() {
+        let mut world = World::new(Vector::new(0.0, 0.0, -10.0));
+        world.add_static(StaticCollider::plane(&Vector::new(0.0, 0.0, 1.0), 0.0));
+        let identity = Quaternion::coords(0.0, 0.0, 0.0, 1.0);
+        let top = RigidBox::new(2.0, 2.0, 0.2, 1.0);
+        let leg = RigidBox::new(0.2, 0.2, 1.0, 1.0);
+        let mut parts: Vec<(&dyn RigidBody, Vector, Quaternion)> =
+            vec![(&top, Vector::new(0.0, 0.0, 1.1), identity.clone())];
+        for (x, y) in [(-0.9, -0.9), (0.9, -0.9), (-0.9, 0.9), (0.9, 0.9)] {
+            parts.push((&leg, Vector::new(x, y, 0.5), identity.clone()));
+        }
+        let table = CompoundBody::new(&parts);
+        let rest_z = table.centroid().z;
+        let t_handle = world.add(
+            &table,
+            &Vector::new(0.0, 0.0, rest_z + 0.1),
+            &identity,
+            &Vector::zero(),
+            &Vector::zero(),
+        );
+        let b_handle = world.add(
+            &RigidBox::new(0.5, 0.5, 0.5, 1.0),
+            &Vector::new(0.3, 0.2, 2.0),
+            &identity,
+            &Vector::zero(),
+            &Vector::zero(),
+        );
+        let mut t = 0.0;
+        for _ in 0..300 {
+            t = world.step(t, 0.01);
+        }
+        let table = world.get(t_handle).unwrap();
+        assert!(
+            (table.position().z - rest_z).abs() < 0.02,
+            "z = {}",
+            table.position().z
+        );
+        assert!(table.angular_velocity().magnitude() < 0.1);
+        let z = world.get(b_handle).unwrap().position().z;
+        assert!((z - 1.45).abs() < 0.03, "z = {}", z);
+    }
+
+    #[test]
+    fn test_sphere_rests_on_static_box() {
+        let mut world = World::new(Vector::new(0.0, 0.0, -10.0));
+        world.add_static(StaticCollider::cuboid(
+            4.0,
+            4.0,
+            1.0,
+            &Vector::new(0.0, 0.0, 0.5),
+            &Quaternion::coords(0.0, 0.0, 0.0, 1.0),
+        ));
+        let handle = world.add(
+            &RigidSphere::new(0.5, 1.0),
+            &Vector::new(0.3, -0.2, 2.0),
+            &Quaternion::coords(0.0, 0.0, 0.0, 1.0),
+            &Vector::zero(),
+            &Vector::zero(),
+        );
+        let mut t = 0.0;
+        for _ in 0..300 {
+            t = world.step(t, 0.01);
+        }
+        let sphere = world.get(handle).unwrap();
+        assert!(
+            (sphere.position().z - 1.5).abs() < 0.02,
+            "z = {}",
+            sphere.position().z
+        );
+        assert!(sphere.linear_velocity().magnitude() < 0.1);
+    }
+
+    #[test]
+    fn test_cylinder_rests_on_its_cap() {
+        let mut world = World::new(Vector::new(0.0, 0.0, -10.0));
+        world.set_sleep_config(SleepConfig {
+            enabled: false,
+            ..SleepConfig::default()
+        });
+        world.add_static(StaticCollider::cuboid(
+            4.0,
+            4.0,
+            1.0,
+            &Vector::new(0.0, 0.0, 0.5),
+            &Quaternion::coords(0.0, 0.0, 0.0, 1.0),
+        ));
+        let handle = world.add(
+            &RigidCylinder::new(0.5, 1.0, 1.0),
+            &Vector::new(0.0, 0.0, 1.6),
+            &Quaternion::coords(0.0, 0.0, 0.0, 1.0),
+            &Vector::zero(),
+            &Vector::zero(),
+        );
+        settle(&mut world, 300);
+        let cylinder = world.get(handle).unwrap();
+        assert!(
+            cylinder.angular_velocity().magnitude() < 0.01,
+            "w = {}",
+            cylinder.angular_velocity().magnitude()
+        );
+        let axis = &cylinder.orientation().to_rotation_matrix() * &Vector::new(0.0, 0.0, 1.0);
+        assert!(axis.z > 0.999, "axis = {:?}", axis);
+        let z = cylinder.position().z;
+        assert!((z - 1.5).abs() < 0.02, "z = {}", z);
+    }
+
+    #[test]
+    fn test_capsule_lands_on_box() {
+        let mut world = World::new(Vector::new(0.0, 0.0, -10.0));
+        world.add_static(StaticCollider::plane(&Vector::new(0.0, 0.0, 1.0), 0.0));
+        world.add(
+            &RigidBox::new(2.0, 2.0, 1.0, 1.0),
+            &Vector::new(0.0, 0.0, 0.5),
+            &Quaternion::coords(0.0, 0.0, 0.0, 1.0),
+            &Vector::zero(),
+            &Vector::zero(),
+        );
+        // lying along x
+        let handle = world.add(
+            &RigidCapsule::new(0.25, 0.5, 1.0),
+            &Vector::new(0.0, 0.0, 2.0),
+            &Quaternion::from_rotation(&Vector::new(0.0, 1.0, 0.0), std::f64::consts::FRAC_PI_2),
+            &Vector::zero(),
+            &Vector::zero(),
+        );
+        let mut t = 0.0;
+        for _ in 0..300 {
+            t = world.step(t, 0.01);
+        }
+        let z = world.get(handle).unwrap().position().z;
+        assert!((z - 1.25).abs() < 0.03, "z = {}", z);
+    }
+
+    #[test]
+    fn test_box_stack_comes_to_rest() {
+        let mut world = World::new(Vector::new(0.0, 0.0, -10.0));
+        world.set_sleep_config(SleepConfig {
+            enabled: false,
+            ..SleepConfig::default()
+        });
+        world.add_static(StaticCollider::plane(&Vector::new(0.0, 0.0, 1.0), 0.0));
+        for z in [0.6, 1.8] {
+            world.add(
+                &RigidBox::new(1.0, 1.0, 1.0, 1.0),
+                &Vector::new(0.0, 0.0, z),
+                &Quaternion::coords(0.0, 0.0, 0.0, 1.0),
+                &Vector::zero(),
+                &Vector::zero(),
+            );
+        }
+        let mut t = 0.0;
+        for _ in 0..300 {
+            t = world.step(t, 0.01);
+        }
+        let top = &world.objects[1].state;
+        assert!((top.x.z - 1.5).abs() < 0.02, "z = {}", top.x.z);
+        assert!(top.v.magnitude() < 0.05, "v = {:?}", top.v);
+    }
+
+    #[test]
+    fn test_box_stack_with_aabb_tree() {
+        let mut world = World::new(Vector::new(0.0, 0.0, -10.0));
+        world.set_broad_phase(DynamicAabbTree::default());
+        world.add_static(StaticCollider::plane(&Vector::new(0.0, 0.0, 1.0), 0.0));
+        for z in [0.6, 1.8, 3.0] {
+            world.add(
+                &RigidBox::new(1.0, 1.0, 1.0, 1.0),
+                &Vector::new(0.0, 0.0, z),
+                &Quaternion::coords(0.0, 0.0, 0.0, 1.0),
+                &Vector::zero(),
+                &Vector::zero(),
+            );
+        }
+        let mut t = 0.0;
+        for _ in 0..300 {
+            t = world.step(t, 0.01);
+        }
+        let top = &world.objects[2].state;
+        assert!((top.x.z - 2.5).abs() < 0.03, "z = {}", top.x.z);
+    }
+
+    #[test]
+    fn test_friction_stops_sliding_box() {
+        let mut world = World::new(Vector::new(0.0, 0.0, -10.0));
+        world.add_static(StaticCollider::plane(&Vector::new(0.0, 0.0, 1.0), 0.0));
+        world.add(
+            &RigidBox::new(1.0, 1.0, 1.0, 1.0),
+            &Vector::new(0.0, 0.0, 0.5),
+            &Quaternion::coords(0.0, 0.0, 0.0, 1.0),
+            &Vector::new(3.0, 0.0, 0.0),
+            &Vector::zero(),
+        );
+        let mut t = 0.0;
+        for _ in 0..200 {
+            t = world.step(t, 0.01);
+        }
+        // a box sliding at 3 m/s with mu = 0.5 and g = 10 stops after 0.9 m
+        let state = &world.objects[0].state;
+        assert!(state.v.x.abs() < 0.01, "v = {:?}", state.v);
+        assert!((state.x.x - 0.9).abs() < 0.1, "x = {}", state.x.x);
+    }
+
+    #[test]
+    fn test_sphere_rolls_on_plane() {
+        let mut world = World::new(Vector::new(0.0, 0.0, -10.0));
+        world.add_static(StaticCollider::plane(&Vector::new(0.0, 0.0, 1.0), 0.0));
+        let handle = world.add(
+            &RigidSphere::new(0.5, 1.0),
+            &Vector::new(0.0, 0.0, 0.5),
+            &Quaternion::coords(0.0, 0.0, 0.0, 1.0),
+            &Vector::new(1.0, 0.0, 0.0),
+            &Vector::zero(),
+        );
+        let mut t = 0.0;
+        for _ in 0..200 {
+            t = world.step(t, 0.01);
+        }
+        // friction turns sliding into rolling at 5/7 of the initial speed
+        let sphere = world.get(handle).unwrap();
+        assert!((sphere.position().z - 0.5).abs() < 0.01);
+        assert!((sphere.linear_velocity().x - 5.0 / 7.0).abs() < 0.02);
+        assert!((sphere.angular_velocity().y - 10.0 / 7.0).abs() < 0.05);
+    }
+
+    #[test]
+    fn test_icy_box_slides_further() {
+        let mut world = World::new(Vector::new(0.0, 0.0, -10.0));
+        world.add_static(StaticCollider::plane(&Vector::new(0.0, 0.0, 1.0), 0.0));
+        let ice = Material::new(0.1, 0.0, 2.0).with_friction_combine(CombineRule::Min);
+        let handle = world.add(
+            &RigidBox::with_material(1.0, 1.0, 1.0, ice),
+            &Vector::new(0.0, 0.0, 0.5),
+            &Quaternion::coords(0.0, 0.0, 0.0, 1.0),
+            &Vector::new(3.0, 0.0, 0.0),
+            &Vector::zero(),
+        );
+        assert_approx_eq!(world.objects[0].inv_mass, 0.5);
+        settle(&mut world, 400);
+        // mu = 0.1 rather than the plane's 0.5 stops it after 4.5 m
+        let body = world.get(handle).unwrap();
+        assert!(body.linear_velocity().x.abs() < 0.01);
+        assert!(
+            (body.position().x - 4.5).abs() < 0.2,
+            "x = {}",
+            body.position().x
+        );
+    }
+
+    #[test]
+    fn test_rolling_friction_stops_sphere() {
+        let mut world = World::new(Vector::new(0.0, 0.0, -10.0));
+        let rough = Material::default().with_rolling_friction(0.05);
+        world.add_static(
+            StaticCollider::plane(&Vector::new(0.0, 0.0, 1.0), 0.0).with_material(rough),
+        );
+        let handle = world.add(
+            &RigidSphere::with_material(0.5, rough),
+            &Vector::new(0.0, 0.0, 0.5),
+            &Quaternion::coords(0.0, 0.0, 0.0, 1.0),
+            &Vector::new(1.0, 0.0, 0.0),
+            &Vector::zero(),
+        );
+        settle(&mut world, 300);
+        let sphere = world.get(handle).unwrap();
+        assert!(sphere.linear_velocity().magnitude() < 0.01);
+        assert!(sphere.angular_velocity().magnitude() < 0.02);
+        assert!(sphere.position().x > 0.1);
+    }
+
+    #[test]
+    fn test_box_rests_with_symplectic_euler() {
+        let mut world = World::new(Vector::new(0.0, 0.0, -10.0));
+        world.set_integrator(SemiImplicitEuler);
+        world.add_static(StaticCollider::plane(&Vector::new(0.0, 0.0, 1.0), 0.0));
+        world.add(
+            &RigidBox::new(1.0, 1.0, 1.0, 1.0),
+            &Vector::new(0.0, 0.0, 2.0),
+            &Quaternion::coords(0.0, 0.0, 0.0, 1.0),
+            &Vector::zero(),
+            &Vector::zero(),
+        );
+        let mut t = 0.0;
+        for _ in 0..300 {
+            t = world.step(t, 0.01);
+        }
+        let state = &world.objects[0].state;
+        assert!((state.x.z - 0.5).abs() < 0.02, "z = {}", state.x.z);
+    }
+
+    #[test]
+    fn test_split_impulse_resting_contact() {
+        let mut world = World::new(Vector::new(0.0, 0.0, -10.0));
+        world.set_sleep_config(SleepConfig {
+            enabled: false,
+            ..SleepConfig::default()
+        });
+        world.set_solver_config(SolverConfig {
+            position_correction: PositionCorrection::SplitImpulse,
+            ..SolverConfig::default()
+        });
+        world.add_static(StaticCollider::plane(&Vector::new(0.0, 0.0, 1.0), 0.0));
+        world.add(
+            &RigidBox::new(1.0, 1.0, 1.0, 1.0),
+            &Vector::new(0.0, 0.0, 0.4),
+            &Quaternion::coords(0.0, 0.0, 0.0, 1.0),
+            &Vector::zero(),
+            &Vector::zero(),
+        );
+        let mut t = 0.0;
+        for _ in 0..100 {
+            t = world.step(t, 0.01);
+        }
+        // the initial penetration is removed without launching the box
+        let state = &world.objects[0].state;
+        assert!((state.x.z - 0.5).abs() < 0.01, "z = {}", state.x.z);
+        assert!(state.v.magnitude() < 0.05, "v = {:?}", state.v);
+    }
+
+    fn free_box(world: &mut World) -> BodyHandle {
+        world.add(
+            &RigidBox::new(1.0, 2.0, 3.0, 2.0),
+            &Vector::zero(),
+            &Quaternion::coords(0.0, 0.0, 0.0, 1.0),
+            &Vector::zero(),
+            &Vector::zero(),
+        )
+    }
+
+    #[test]
+    fn test_force_is_cleared_after_step() {
+        let mut world = World::new(Vector::zero());
+        let handle = free_box(&mut world);
+        world.apply_force(handle, &Vector::new(12.0, 0.0, 0.0));
+        world.step(0.0, 0.5);
+        // mass 12, so 1 m/s^2 for half a second
+        let state = &world.objects[0].state;
+        assert_approx_eq!(state.v, Vector::new(0.5, 0.0, 0.0));
+        assert_approx_eq!(state.x, Vector::new(0.125, 0.0, 0.0));
+        world.step(0.5, 0.5);
+        assert_approx_eq!(world.objects[0].state.v, Vector::new(0.5, 0.0, 0.0));
+    }
+
+    #[test]
+    fn test_heavy_and_light_bodies_fall_alike() {
+        let mut world = World::new(Vector::new(0.0, 0.0, -10.0));
+        let light = world.add(
+            &RigidBox::new(1.0, 1.0, 1.0, 0.5),
+            &Vector::new(5.0, 0.0, 0.0),
+            &Quaternion::coords(0.0, 0.0, 0.0, 1.0),
+            &Vector::zero(),
+            &Vector::zero(),
+        );
+        let heavy = free_box(&mut world);
+        world.step(0.0, 0.5);
+        for handle in [light, heavy] {
+            let body = world.get(handle).unwrap();
+            assert_approx_eq!(body.linear_velocity(), &Vector::new(0.0, 0.0, -5.0));
+            assert_approx_eq!(body.position().z, -1.25);
         }
     }
+
+    #[test]
+    fn test_force_at_point_adds_torque() {
+        let mut world = World::new(Vector::zero());
+        let handle = free_box(&mut world);
+        world.apply_force_at_point(
+            handle,
+            &Vector::new(0.0, 1.0, 0.0),
+            &Vector::new(1.0, 0.0, 0.0),
+        );
+        world.apply_torque(handle, &Vector::new(0.0, 0.0, 1.0));
+        world.step(0.0, 0.1);
+        let state = &world.objects[0].state;
+        assert_approx_eq!(state.p, Vector::new(0.0, 0.1, 0.0));
+        assert_approx_eq!(state.l, Vector::new(0.0, 0.0, 0.2));
+    }
+
+    #[test]
+    fn test_impulse_changes_momentum_immediately() {
+        let mut world = World::new(Vector::zero());
+        let handle = free_box(&mut world);
+        world.apply_impulse(
+            handle,
+            &Vector::new(0.0, 0.0, 6.0),
+            &Vector::new(0.0, 1.0, 0.0),
+        );
+        let state = &world.objects[0].state;
+        assert_approx_eq!(state.v, Vector::new(0.0, 0.0, 0.5));
+        assert_approx_eq!(state.l, Vector::new(6.0, 0.0, 0.0));
+    }
+
+    fn add_box(world: &mut World, x: f64) -> BodyHandle {
+        world.add(
+            &RigidBox::new(1.0, 1.0, 1.0, 2.0),
+            &Vector::new(x, 0.0, 0.0),
+            &Quaternion::coords(0.0, 0.0, 0.0, 1.0),
+            &Vector::zero(),
+            &Vector::zero(),
+        )
+    }
+
+    fn add_box_at_height(world: &mut World, z: f64) -> BodyHandle {
+        let handle = add_box(world, 0.0);
+        world
+            .get_mut(handle)
+            .unwrap()
+            .set_position(&Vector::new(0.0, 0.0, z));
+        handle
+    }
+
+    #[test]
+    fn test_removed_body_is_gone() {
+        let mut world = World::new(Vector::zero());
+        let a = add_box(&mut world, 1.0);
+        let b = add_box(&mut world, 2.0);
+        let c = add_box(&mut world, 3.0);
+        assert!(world.remove(a));
+        assert!(!world.remove(a));
+        assert!(!world.contains(a));
+        assert!(world.get(a).is_none());
+        // the remaining bodies are still found after being moved around
+        assert_approx_eq!(world.get(b).unwrap().position().x, 2.0);
+        assert_approx_eq!(world.get(c).unwrap().position().x, 3.0);
+        let d = add_box(&mut world, 4.0);
+        assert_ne!(a, d);
+        assert!(world.get(a).is_none());
+        assert_approx_eq!(world.get(d).unwrap().position().x, 4.0);
+    }
+
+    #[test]
+    fn test_body_accessors() {
+        let mut world = World::new(Vector::zero());
+        let handle = add_box(&mut world, 0.0);
+        let body = world.get_mut(handle).unwrap();
+        assert_approx_eq!(body.mass(), 2.0);
+        assert_approx_eq!(
+            body.inertia_tensor(),
+            Matrix::new([
+                1.0 / 3.0,
+                0.0,
+                0.0,
+                0.0,
+                1.0 / 3.0,
+                0.0,
+                0.0,
+                0.0,
+                1.0 / 3.0
+            ])
+        );
+        body.set_linear_velocity(&Vector::new(1.0, 2.0, 3.0));
+        body.set_angular_velocity(&Vector::new(0.0, 0.0, 1.5));
+        body.set_position(&Vector::new(0.0, 5.0, 0.0));
+        let body = world.get(handle).unwrap();
+        assert_approx_eq!(body.linear_velocity(), &Vector::new(1.0, 2.0, 3.0));
+        assert_approx_eq!(body.angular_velocity(), &Vector::new(0.0, 0.0, 1.5));
+        assert_approx_eq!(body.position(), &Vector::new(0.0, 5.0, 0.0));
+    }
+
+    #[test]
+    fn test_advance_runs_fixed_steps() {
+        let mut world = World::new(Vector::zero());
+        world.set_fixed_timestep(0.01);
+        let handle = add_box(&mut world, 0.0);
+        world
+            .get_mut(handle)
+            .unwrap()
+            .set_linear_velocity(&Vector::new(1.0, 0.0, 0.0));
+        assert_eq!(world.advance(0.025), 2);
+        assert_approx_eq!(world.time(), 0.02);
+        assert_approx_eq!(world.interpolation_alpha(), 0.5);
+        let (x, _) = world.interpolated_pose(handle).unwrap();
+        assert_approx_eq!(x, Vector::new(0.015, 0.0, 0.0));
+        assert_eq!(world.advance(0.005), 1);
+        assert_approx_eq!(world.interpolation_alpha(), 0.0);
+    }
+
+    #[test]
+    fn test_advance_caps_substeps() {
+        let mut world = World::new(Vector::zero());
+        world.set_fixed_timestep(0.01);
+        world.set_max_substeps(4);
+        assert_eq!(world.advance(1.0), 4);
+        assert_approx_eq!(world.time(), 0.04);
+        assert!(world.interpolation_alpha() < 1.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid elapsed time")]
+    fn test_nan_elapsed_time_is_rejected() {
+        World::new(Vector::zero()).advance(f64::NAN);
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid fixed timestep")]
+    fn test_zero_timestep_is_rejected() {
+        World::new(Vector::zero()).set_fixed_timestep(0.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one substep")]
+    fn test_zero_substeps_are_rejected() {
+        World::new(Vector::zero()).set_max_substeps(0);
+    }
+
+    #[test]
+    fn test_interpolated_orientation() {
+        let mut world = World::new(Vector::zero());
+        world.set_fixed_timestep(0.1);
+        let handle = add_box(&mut world, 0.0);
+        world
+            .get_mut(handle)
+            .unwrap()
+            .set_angular_velocity(&Vector::new(0.0, 0.0, 1.0));
+        world.advance(0.15);
+        // halfway between the poses at 0 and 0.1
+        let (_, q) = world.interpolated_pose(handle).unwrap();
+        let expected = Quaternion::from_rotation(&Vector::new(0.0, 0.0, 1.0), 0.05);
+        assert_approx_eq!(q.to_rotation_matrix(), expected.to_rotation_matrix());
+    }
+
+    // A box whose body origin is two units from its center
+    struct OffsetBox(RigidBox);
+
+    impl RigidBody for OffsetBox {
+        fn mass(&self) -> f64 {
+            self.0.mass()
+        }
+        fn inertia_tensor(&self) -> Matrix {
+            self.0.inertia_tensor()
+        }
+        fn shape(&self) -> Shape {
+            Shape::Compound {
+                children: vec![ShapeChild {
+                    position: Vector::new(2.0, 0.0, 0.0),
+                    orientation: Quaternion::coords(0.0, 0.0, 0.0, 1.0),
+                    shape: self.0.shape(),
+                }],
+            }
+        }
+        fn center_of_mass(&self) -> Vector {
+            Vector::new(2.0, 0.0, 0.0)
+        }
+    }
+
+    #[test]
+    fn test_off_center_body_lands_on_plane() {
+        let mut world = World::new(Vector::new(0.0, 0.0, -10.0));
+        world.add_static(StaticCollider::plane(&Vector::new(0.0, 0.0, 1.0), 0.0));
+        let handle = world.add(
+            &OffsetBox(RigidBox::new(1.0, 1.0, 0.2, 1.0)),
+            &Vector::new(0.0, 0.0, 1.0),
+            &Quaternion::coords(0.0, 0.0, 0.0, 1.0),
+            &Vector::zero(),
+            &Vector::zero(),
+        );
+        let object = world.get(handle).unwrap();
+        assert_approx_eq!(object.position(), Vector::new(0.0, 0.0, 1.0));
+        assert_approx_eq!(object.center_of_mass(), &Vector::new(2.0, 0.0, 1.0));
+        assert!(matches!(object.shape(), Shape::Compound { children } if children.len() == 1));
+        settle(&mut world, 300);
+        let object = world.get(handle).unwrap();
+        let z = object.center_of_mass().z;
+        assert!((z - 0.1).abs() < 0.02, "z = {}", z);
+        let x = object.position();
+        assert!(
+            (&x - &Vector::new(0.0, 0.0, 0.1)).magnitude() < 0.05,
+            "x = {:?}",
+            x
+        );
+    }
+
+    #[test]
+    fn test_off_center_body_is_posed_by_its_origin() {
+        let mut world = World::new(Vector::zero());
+        let identity = Quaternion::coords(0.0, 0.0, 0.0, 1.0);
+        let body = OffsetBox(RigidBox::new(1.0, 1.0, 1.0, 1.0));
+        let handle = world.add(
+            &body,
+            &Vector::zero(),
+            &identity,
+            &Vector::zero(),
+            &Vector::zero(),
+        );
+        let turn =
+            Quaternion::from_rotation(&Vector::new(0.0, 0.0, 1.0), std::f64::consts::FRAC_PI_2);
+        let object = world.get_mut(handle).unwrap();
+        object.set_orientation(&turn);
+        // turned about the origin, which swings the center round to +y
+        assert_approx_eq!(object.position(), Vector::zero());
+        assert_approx_eq!(object.center_of_mass(), &Vector::new(0.0, 2.0, 0.0));
+        object.set_position(&Vector::new(1.0, 0.0, 0.0));
+        assert_approx_eq!(object.center_of_mass(), &Vector::new(1.0, 2.0, 0.0));
+        assert_approx_eq!(
+            object.orientation().to_rotation_matrix(),
+            turn.to_rotation_matrix()
+        );
+        let (x, _) = world.interpolated_pose(handle).unwrap();
+        assert_approx_eq!(x, Vector::new(1.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn test_asymmetric_body_spins_about_principal_axis() {
+        let mut world = World::new(Vector::zero());
+        let tilt = Quaternion::from_rotation(&Vector::new(1.0, 1.0, 1.0).normalize(), 0.5);
+        let bar = RigidBox::new(1.0, 2.0, 3.0, 1.0);
+        let body = CompoundBody::new(&[(&bar, Vector::zero(), tilt.clone())]);
+        // spin about the bar's long axis, which is a principal axis
+        let w = &tilt.to_rotation_matrix().column(2) * 2.0;
+        let l = &body.inertia_tensor() * &w;
+        let identity = Quaternion::coords(0.0, 0.0, 0.0, 1.0);
+        let handle = world.add(&body, &Vector::zero(), &identity, &Vector::zero(), &l);
+        let object = world.get(handle).unwrap();
+        assert_approx_eq!(object.angular_velocity(), &w);
+        assert_approx_eq!(object.inertia_tensor(), body.inertia_tensor());
+        assert_approx_eq!(
+            object.orientation().to_rotation_matrix(),
+            Matrix::identity()
+        );
+        // the shape is the one given, not wrapped for the principal frame
+        assert!(matches!(object.shape(), Shape::Compound { children }
+            if children[0].orientation.approx_eq(&tilt)));
+        world.for_each_object(|_, x, q| {
+            assert_approx_eq!(x, &Vector::zero());
+            assert_approx_eq!(q.to_rotation_matrix(), Matrix::identity());
+        });
+        let mut t = 0.0;
+        for _ in 0..100 {
+            t = world.step(t, 0.01);
+        }
+        let spin = world.get(handle).unwrap().angular_velocity();
+        assert!((spin - &w).magnitude() < 1e-6, "w = {:?}", spin);
+    }
+
+    #[test]
+    fn test_box_lands_on_static_body() {
+        let mut world = World::new(Vector::new(0.0, 0.0, -10.0));
+        let floor = add_box_at_height(&mut world, 0.0);
+        world
+            .get_mut(floor)
+            .unwrap()
+            .set_body_type(BodyType::Static);
+        let b = add_box_at_height(&mut world, 1.5);
+        let mut t = 0.0;
+        for _ in 0..200 {
+            t = world.step(t, 0.01);
+        }
+        assert_approx_eq!(world.get(floor).unwrap().position(), &Vector::zero());
+        let z = world.get(b).unwrap().position().z;
+        assert!((z - 1.0).abs() < 0.02, "z = {}", z);
+    }
+
+    #[test]
+    fn test_static_and_kinematic_bodies_ignore_impulses() {
+        let mut world = World::new(Vector::new(0.0, 0.0, -10.0));
+        let floor = add_box_at_height(&mut world, 0.0);
+        let ground = world.get_mut(floor).unwrap();
+        ground.set_body_type(BodyType::Static);
+        ground.set_linear_velocity(&Vector::new(3.0, 0.0, 0.0));
+        let platform = add_box_at_height(&mut world, 0.0);
+        let lift = world.get_mut(platform).unwrap();
+        lift.set_position(&Vector::new(3.0, 0.0, 0.0));
+        lift.set_body_type(BodyType::Kinematic);
+        let b = add_box_at_height(&mut world, 1.0);
+        for handle in [floor, platform] {
+            let center = world.get(handle).unwrap().position();
+            world.apply_impulse(handle, &Vector::new(0.0, 0.0, 5.0), &center);
+            world.apply_force(handle, &Vector::new(0.0, 0.0, 50.0));
+        }
+        settle(&mut world, 100);
+        for handle in [floor, platform] {
+            let body = world.get(handle).unwrap();
+            assert_approx_eq!(body.linear_velocity(), &Vector::zero());
+            assert_approx_eq!(body.angular_velocity(), &Vector::zero());
+        }
+        assert_approx_eq!(world.get(floor).unwrap().position(), &Vector::zero());
+        let resting = world.get(b).unwrap();
+        assert!(resting.linear_velocity().magnitude() < 0.05);
+        assert!((resting.position().z - 1.0).abs() < 0.02);
+    }
+
+    #[test]
+    fn test_kinematic_platform_lifts_box() {
+        let mut world = World::new(Vector::new(0.0, 0.0, -10.0));
+        let platform = add_box_at_height(&mut world, 0.0);
+        let lift = world.get_mut(platform).unwrap();
+        lift.set_body_type(BodyType::Kinematic);
+        lift.set_linear_velocity(&Vector::new(0.0, 0.0, 0.5));
+        let b = add_box_at_height(&mut world, 1.0);
+        let mut t = 0.0;
+        for _ in 0..200 {
+            t = world.step(t, 0.01);
+        }
+        let lift = world.get(platform).unwrap();
+        // not slowed down by the load or gravity
+        assert_approx_eq!(lift.linear_velocity(), &Vector::new(0.0, 0.0, 0.5));
+        assert!((lift.position().z - 1.0).abs() < 1e-6);
+        let z = world.get(b).unwrap().position().z;
+        assert!((z - 2.0).abs() < 0.03, "z = {}", z);
+    }
+
+    #[test]
+    fn test_kinematic_target_pose() {
+        let mut world = World::new(Vector::new(0.0, 0.0, -10.0));
+        let h = add_box_at_height(&mut world, 0.0);
+        let object = world.get_mut(h).unwrap();
+        object.set_body_type(BodyType::Kinematic);
+        let q = Quaternion::from_rotation(&Vector::new(0.0, 0.0, 1.0), 0.1);
+        object.set_target_pose(&Vector::new(0.1, 0.0, 0.0), &q);
+        world.step(0.0, 0.1);
+        let object = world.get(h).unwrap();
+        assert_approx_eq!(object.position(), &Vector::new(0.1, 0.0, 0.0));
+        assert_approx_eq!(
+            object.orientation().to_rotation_matrix(),
+            q.to_rotation_matrix()
+        );
+        assert_approx_eq!(object.linear_velocity(), &Vector::new(1.0, 0.0, 0.0));
+        assert_approx_eq!(object.angular_velocity(), &Vector::new(0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn test_kinematic_body_pushes_dynamic_body() {
+        let mut world = World::new(Vector::new(0.0, 0.0, -10.0));
+        world.add_static(StaticCollider::plane(&Vector::new(0.0, 0.0, 1.0), 0.0));
+        let pusher = add_box_at_height(&mut world, 0.5);
+        let object = world.get_mut(pusher).unwrap();
+        object.set_body_type(BodyType::Kinematic);
+        object.set_position(&Vector::new(-1.5, 0.0, 0.5));
+        object.set_linear_velocity(&Vector::new(1.0, 0.0, 0.0));
+        let pushed = add_box_at_height(&mut world, 0.5);
+        let mut t = 0.0;
+        for _ in 0..200 {
+            t = world.step(t, 0.01);
+        }
+        assert_approx_eq!(world.get(pusher).unwrap().position().x, 0.5);
+        let x = world.get(pushed).unwrap().position().x;
+        assert!(x > 1.45, "x = {}", x);
+    }
+
+    fn settle(world: &mut World, steps: usize) {
+        for _ in 0..steps {
+            world.step(0.0, 0.01);
+        }
+    }
+
+    #[test]
+    fn test_resting_box_falls_asleep() {
+        let mut world = World::new(Vector::new(0.0, 0.0, -10.0));
+        world.add_static(StaticCollider::plane(&Vector::new(0.0, 0.0, 1.0), 0.0));
+        let h = add_box_at_height(&mut world, 1.0);
+        settle(&mut world, 20);
+        assert_eq!(world.is_asleep(h), Some(false));
+        settle(&mut world, 200);
+        let object = world.get(h).unwrap();
+        assert!(object.is_asleep());
+        assert_approx_eq!(object.linear_velocity(), &Vector::zero());
+        let z = object.position().z;
+        settle(&mut world, 100);
+        assert_approx_eq!(world.get(h).unwrap().position().z, z);
+    }
+
+    #[test]
+    fn test_force_wakes_sleeping_body() {
+        let mut world = World::new(Vector::new(0.0, 0.0, -10.0));
+        world.add_static(StaticCollider::plane(&Vector::new(0.0, 0.0, 1.0), 0.0));
+        let h = add_box_at_height(&mut world, 0.5);
+        settle(&mut world, 200);
+        assert_eq!(world.is_asleep(h), Some(true));
+        world.apply_force(h, &Vector::new(0.0, 0.0, 100.0));
+        assert_eq!(world.is_asleep(h), Some(false));
+        world.step(0.0, 0.01);
+        assert!(world.get(h).unwrap().linear_velocity().z > 0.0);
+    }
+
+    #[test]
+    fn test_new_material_or_ccd_wakes_sleeping_body() {
+        let mut world = World::new(Vector::new(0.0, 0.0, -10.0));
+        world.add_static(StaticCollider::plane(&Vector::new(0.0, 0.0, 1.0), 0.0));
+        let h = add_box_at_height(&mut world, 0.5);
+        settle(&mut world, 200);
+        assert_eq!(world.is_asleep(h), Some(true));
+        let ice = Material::new(0.05, 0.0, 1.0);
+        world.get_mut(h).unwrap().set_material(Some(ice));
+        assert_eq!(world.is_asleep(h), Some(false));
+        settle(&mut world, 100);
+        assert_eq!(world.is_asleep(h), Some(true));
+        world.get_mut(h).unwrap().set_ccd_enabled(true);
+        assert_eq!(world.is_asleep(h), Some(false));
+    }
+
+    #[test]
+    fn test_falling_box_wakes_sleeping_body() {
+        let mut world = World::new(Vector::new(0.0, 0.0, -10.0));
+        world.add_static(StaticCollider::plane(&Vector::new(0.0, 0.0, 1.0), 0.0));
+        let below = add_box_at_height(&mut world, 0.5);
+        settle(&mut world, 200);
+        assert_eq!(world.is_asleep(below), Some(true));
+        let above = add_box_at_height(&mut world, 3.0);
+        // awake while the other box falls towards it
+        settle(&mut world, 65);
+        assert_eq!(world.is_asleep(below), Some(false));
+        settle(&mut world, 300);
+        assert_eq!(world.is_asleep(below), Some(true));
+        assert_eq!(world.is_asleep(above), Some(true));
+        let z = world.get(above).unwrap().position().z;
+        assert!((z - 1.5).abs() < 0.05, "z = {}", z);
+    }
+
+    #[test]
+    fn test_removed_joints_free_their_slots() {
+        let mut world = World::new(Vector::zero());
+        let a = add_box(&mut world, 0.0);
+        let b = add_box(&mut world, 2.0);
+        let kept = world
+            .add_joint(Some(a), b, &Joint::ball(&Vector::new(1.0, 0.0, 0.0)))
+            .unwrap();
+        let mut old = None;
+        for _ in 0..100 {
+            let joint = world
+                .add_joint(None, b, &Joint::ball(&Vector::new(2.0, 0.0, 0.0)))
+                .unwrap();
+            assert_ne!(Some(joint), old);
+            assert!(world.remove_joint(joint));
+            old = Some(joint);
+        }
+        assert_eq!(world.joints.len(), 1);
+        let old = old.unwrap();
+        assert!(!world.remove_joint(old));
+        assert!(!world.set_joint_motor(old, None));
+        assert!(world.joint_position(old).is_none());
+        // the last joint moves into the slot of a removed one
+        let c = add_box(&mut world, 4.0);
+        let moved = world
+            .add_joint(Some(b), c, &Joint::ball(&Vector::new(3.0, 0.0, 0.0)))
+            .unwrap();
+        assert!(world.remove_joint(kept));
+        assert!(world.set_joint_motor(moved, None));
+        // removing a body removes its joints
+        world.remove(c);
+        assert!(!world.remove_joint(moved));
+        assert!(world.joints.is_empty());
+    }
+
+    #[test]
+    fn test_jointed_bodies_sleep_and_wake_together() {
+        let mut world = World::new(Vector::new(0.0, 0.0, -10.0));
+        let a = add_box_at_height(&mut world, -1.0);
+        let b = add_box_at_height(&mut world, -3.0);
+        world.add_joint(None, a, &Joint::ball(&Vector::zero()));
+        world.add_joint(Some(a), b, &Joint::ball(&Vector::new(0.0, 0.0, -2.0)));
+        settle(&mut world, 200);
+        assert_eq!(world.is_asleep(a), Some(true));
+        assert_eq!(world.is_asleep(b), Some(true));
+        world.apply_impulse(b, &Vector::new(1.0, 0.0, 0.0), &Vector::new(0.0, 0.0, -3.0));
+        world.step(0.0, 0.01);
+        assert_eq!(world.is_asleep(a), Some(false));
+        assert!(world.get(a).unwrap().angular_velocity().magnitude() > 0.0);
+    }
+
+    #[test]
+    fn test_sleeping_can_be_disabled() {
+        let mut world = World::new(Vector::new(0.0, 0.0, -10.0));
+        world.add_static(StaticCollider::plane(&Vector::new(0.0, 0.0, 1.0), 0.0));
+        world.set_sleep_config(SleepConfig {
+            enabled: false,
+            ..SleepConfig::default()
+        });
+        let h = add_box_at_height(&mut world, 0.5);
+        settle(&mut world, 300);
+        assert_eq!(world.is_asleep(h), Some(false));
+    }
+
+    #[test]
+    fn test_ray_hits_nearest_collider() {
+        let mut world = World::new(Vector::zero());
+        let ground = world.add_static(StaticCollider::plane(&Vector::new(0.0, 0.0, 1.0), -0.5));
+        let near = add_box(&mut world, 2.0);
+        let far = add_box(&mut world, 5.0);
+        let origin = Vector::new(-3.0, 0.0, 0.0);
+        let along_x = Vector::new(2.0, 0.0, 0.0);
+        let hit = world
+            .cast_ray(&origin, &along_x, 100.0, &QueryFilter::new())
+            .unwrap();
+        assert_eq!(hit.collider, Collider::Body(near));
+        assert_approx_eq!(hit.distance, 4.5);
+        assert_approx_eq!(hit.point, Vector::new(1.5, 0.0, 0.0));
+        assert_approx_eq!(hit.normal, Vector::new(-1.0, 0.0, 0.0));
+        let filter = QueryFilter::new().excluding(near);
+        let hit = world.cast_ray(&origin, &along_x, 100.0, &filter).unwrap();
+        assert_eq!(hit.collider, Collider::Body(far));
+        assert!(world.cast_ray(&origin, &along_x, 4.0, &filter).is_none());
+        let down = Vector::new(0.1, 0.0, -1.0);
+        let hits = world.cast_ray_all(
+            &Vector::new(1.5, 0.0, 3.0),
+            &down,
+            100.0,
+            &QueryFilter::new(),
+        );
+        let colliders: Vec<Collider> = hits.iter().map(|hit| hit.collider).collect();
+        assert_eq!(colliders, [Collider::Body(near), Collider::Static(ground)]);
+        let filter = QueryFilter::new().without_statics();
+        assert_eq!(
+            world
+                .cast_ray_all(&Vector::new(1.5, 0.0, 3.0), &down, 100.0, &filter)
+                .len(),
+            1
+        );
+        for nowhere in [Vector::zero(), Vector::new(f64::NAN, 0.0, 0.0)] {
+            assert!(world
+                .cast_ray_all(&origin, &nowhere, 100.0, &QueryFilter::new())
+                .is_empty());
+        }
+    }
+
+    #[test]
+    fn test_ray_follows_body_orientation() {
+        let mut world = World::new(Vector::zero());
+        let h = add_box(&mut world, 0.0);
+        let q = Quaternion::from_rotation(&Vector::new(0.0, 0.0, 1.0), std::f64::consts::FRAC_PI_4);
+        world.get_mut(h).unwrap().set_orientation(&q);
+        let hit = world
+            .cast_ray(
+                &Vector::new(-3.0, 0.0, 0.0),
+                &Vector::new(1.0, 0.0, 0.0),
+                10.0,
+                &QueryFilter::new(),
+            )
+            .unwrap();
+        // the corner of the turned box points at the ray
+        assert_approx_eq!(hit.distance, 3.0 - 0.5f64.sqrt());
+    }
+
+    #[test]
+    fn test_ray_finds_teleported_body() {
+        let mut world = World::new(Vector::zero());
+        let h = add_box(&mut world, 0.0);
+        world
+            .get_mut(h)
+            .unwrap()
+            .set_position(&Vector::new(20.0, 0.0, 0.0));
+        // queries only borrow the world
+        let body = world.get(h).unwrap();
+        let origin = Vector::new(15.0, 0.0, 0.0);
+        let direction = Vector::new(1.0, 0.0, 0.0);
+        let hit = world
+            .cast_ray(&origin, &direction, 10.0, &QueryFilter::new())
+            .unwrap();
+        assert_eq!(hit.collider, Collider::Body(body.handle()));
+        assert_approx_eq!(hit.distance, 4.5);
+        let back = world.cast_ray(&origin, &-&direction, 10.0, &QueryFilter::new());
+        assert!(back.is_none());
+    }
+
+    #[test]
+    fn test_swept_box_hits_first_obstacle() {
+        let mut world = World::new(Vector::zero());
+        let ground = world.add_static(StaticCollider::plane(&Vector::new(0.0, 0.0, 1.0), -0.5));
+        let wall = add_box(&mut world, 3.0);
+        let shape = Shape::Box {
+            half_extents: Vector::new(0.25, 0.25, 0.25),
+        };
+        let identity = Quaternion::coords(0.0, 0.0, 0.0, 1.0);
+        let start = Vector::new(0.0, 0.0, 0.0);
+        let along_x = Vector::new(1.0, 0.0, 0.0);
+        let hit = world
+            .cast_shape(
+                &shape,
+                &start,
+                &identity,
+                &along_x,
+                10.0,
+                &QueryFilter::new().without_statics(),
+            )
+            .unwrap();
+        assert_eq!(hit.collider, Collider::Body(wall));
+        assert!(
+            (hit.distance - 2.25).abs() < 1e-6,
+            "distance = {}",
+            hit.distance
+        );
+        assert_approx_eq!(hit.normal, Vector::new(-1.0, 0.0, 0.0));
+        let filter = QueryFilter::new().without_statics().excluding(wall);
+        assert!(world
+            .cast_shape(&shape, &start, &identity, &along_x, 10.0, &filter)
+            .is_none());
+        // falling onto the ground
+        let down = Vector::new(0.0, 0.0, -1.0);
+        let hit = world
+            .cast_shape(&shape, &start, &identity, &down, 10.0, &QueryFilter::new())
+            .unwrap();
+        assert_eq!(hit.collider, Collider::Static(ground));
+        assert_approx_eq!(hit.distance, 0.25);
+        assert_approx_eq!(hit.point.z, -0.5);
+        let nowhere = Vector::zero();
+        assert!(world
+            .cast_shape(
+                &shape,
+                &start,
+                &identity,
+                &nowhere,
+                10.0,
+                &QueryFilter::new()
+            )
+            .is_none());
+    }
+
+    #[test]
+    fn test_shape_and_aabb_overlap_queries() {
+        let mut world = World::new(Vector::zero());
+        let ground = world.add_static(StaticCollider::plane(&Vector::new(0.0, 0.0, 1.0), -0.5));
+        let a = add_box(&mut world, 0.0);
+        let b = add_box(&mut world, 1.5);
+        let c = add_box(&mut world, 5.0);
+        let identity = Quaternion::coords(0.0, 0.0, 0.0, 1.0);
+        let sphere = Shape::Sphere { radius: 0.5 };
+        let at = Vector::new(0.9, 0.0, -0.1);
+        let found = world.intersect_shape(&sphere, &at, &identity, &QueryFilter::new());
+        assert_eq!(
+            found,
+            [
+                Collider::Static(ground),
+                Collider::Body(a),
+                Collider::Body(b)
+            ]
+        );
+        // high above the ground, between a and b but touching neither
+        let above = Vector::new(0.75, 0.0, 1.0);
+        let found = world.intersect_shape(&sphere, &above, &identity, &QueryFilter::new());
+        assert!(found.is_empty());
+        let aabb = Aabb::new(Vector::new(1.0, -1.0, -1.0), Vector::new(6.0, 1.0, 1.0));
+        let found = world.intersect_aabb(&aabb, &QueryFilter::new().without_statics());
+        assert_eq!(found, [Collider::Body(b), Collider::Body(c)]);
+        let filter = QueryFilter::new().without_body_type(BodyType::Dynamic);
+        assert_eq!(
+            world.intersect_aabb(&aabb, &filter),
+            [Collider::Static(ground)]
+        );
+        // moved out of the box by hand, and seen through a shared borrow
+        world
+            .get_mut(c)
+            .unwrap()
+            .set_position(&Vector::new(9.0, 0.0, 0.0));
+        let world = &world;
+        let found = world.intersect_aabb(&aabb, &QueryFilter::new().without_statics());
+        assert_eq!(found, [Collider::Body(b)]);
+        let hit = world.cast_shape(
+            &sphere,
+            &Vector::new(6.0, 0.0, 0.0),
+            &identity,
+            &Vector::new(1.0, 0.0, 0.0),
+            5.0,
+            &QueryFilter::new().without_statics(),
+        );
+        assert_eq!(hit.map(|hit| hit.collider), Some(Collider::Body(c)));
+    }
+
+    #[test]
+    fn test_collision_groups_let_bodies_pass() {
+        const PLAYER: u32 = 1;
+        const PROJECTILE: u32 = 2;
+        let mut world = World::new(Vector::zero());
+        let player = add_box(&mut world, 0.0);
+        let projectile = add_box(&mut world, 0.8);
+        world
+            .get_mut(player)
+            .unwrap()
+            .set_collision_groups(CollisionGroups::new(PLAYER, !PROJECTILE));
+        world
+            .get_mut(projectile)
+            .unwrap()
+            .set_collision_groups(CollisionGroups::new(PROJECTILE, u32::MAX));
+        world.step(0.0, 0.01);
+        // overlapping, yet neither pushed the other
+        assert_approx_eq!(
+            world.get(player).unwrap().linear_velocity(),
+            &Vector::zero()
+        );
+        let origin = Vector::new(-3.0, 0.0, 0.0);
+        let along_x = Vector::new(1.0, 0.0, 0.0);
+        let filter = QueryFilter::new().with_groups(CollisionGroups::new(PROJECTILE, u32::MAX));
+        let hit = world.cast_ray(&origin, &along_x, 10.0, &filter).unwrap();
+        assert_eq!(hit.collider, Collider::Body(projectile));
+    }
+
+    #[test]
+    fn test_static_groups_filter_bodies() {
+        const DEBRIS: u32 = 1;
+        const TRIGGER: u32 = 2;
+        let mut world = World::new(Vector::new(0.0, 0.0, -10.0));
+        world.add_static(
+            StaticCollider::plane(&Vector::new(0.0, 0.0, 1.0), 0.0)
+                .with_groups(CollisionGroups::new(TRIGGER, u32::MAX)),
+        );
+        let debris = add_box_at_height(&mut world, 0.5);
+        world
+            .get_mut(debris)
+            .unwrap()
+            .set_collision_groups(CollisionGroups::new(DEBRIS, !TRIGGER));
+        settle(&mut world, 50);
+        assert!(world.get(debris).unwrap().position().z < 0.0);
+    }
+
+    struct OneWayPlatform;
+
+    impl ContactHooks for OneWayPlatform {
+        // platforms are static and only hold up bodies coming from above
+        fn modify_contact(&self, a: Collider, _: Collider, manifold: &mut ContactManifold) {
+            if matches!(a, Collider::Static(_)) && manifold.normal.z < 0.5 {
+                manifold.points.clear();
+            }
+        }
+    }
+
+    #[test]
+    fn test_hooks_modify_contacts() {
+        let mut world = World::new(Vector::new(0.0, 0.0, -10.0));
+        world.add_static(StaticCollider::cuboid(
+            4.0,
+            4.0,
+            0.2,
+            &Vector::new(0.0, 0.0, 1.0),
+            &Quaternion::coords(0.0, 0.0, 0.0, 1.0),
+        ));
+        world.set_contact_hooks(OneWayPlatform);
+        // jumps up through the platform, then lands on it
+        let h = add_box_at_height(&mut world, -1.0);
+        world
+            .get_mut(h)
+            .unwrap()
+            .set_linear_velocity(&Vector::new(0.0, 0.0, 8.0));
+        let mut highest: f64 = 0.0;
+        for _ in 0..300 {
+            world.step(0.0, 0.01);
+            highest = highest.max(world.get(h).unwrap().position().z);
+        }
+        assert!(highest > 2.0, "highest = {}", highest);
+        let z = world.get(h).unwrap().position().z;
+        assert!((z - 1.6).abs() < 0.05, "z = {}", z);
+    }
+
+    struct NoPairs;
+
+    impl ContactHooks for NoPairs {
+        fn filter_pair(&self, _: Collider, _: Collider) -> bool {
+            false
+        }
+    }
+
+    #[test]
+    fn test_hooks_veto_pairs() {
+        let mut world = World::new(Vector::new(0.0, 0.0, -10.0));
+        world.add_static(StaticCollider::plane(&Vector::new(0.0, 0.0, 1.0), 0.0));
+        let h = add_box_at_height(&mut world, 0.5);
+        world.set_contact_hooks(NoPairs);
+        settle(&mut world, 20);
+        assert!(world.get(h).unwrap().position().z < 0.4);
+        world.clear_contact_hooks();
+        let z = world.get(h).unwrap().position().z;
+        settle(&mut world, 100);
+        assert!(world.get(h).unwrap().position().z > z);
+    }
+
+    #[test]
+    fn test_body_falls_through_static_sensor() {
+        let mut world = World::new(Vector::new(0.0, 0.0, -10.0));
+        let zone = world.add_static(
+            StaticCollider::cuboid(
+                4.0,
+                4.0,
+                1.0,
+                &Vector::zero(),
+                &Quaternion::coords(0.0, 0.0, 0.0, 1.0),
+            )
+            .as_sensor(),
+        );
+        let zone = Collider::Static(zone);
+        let h = add_box_at_height(&mut world, 2.0);
+        let mut events = Vec::new();
+        for _ in 0..100 {
+            world.step(0.0, 0.01);
+            events.extend(world.sensor_events().iter().copied());
+        }
+        assert_eq!(
+            events,
+            [
+                SensorEvent::Enter {
+                    sensor: zone,
+                    body: h
+                },
+                SensorEvent::Exit {
+                    sensor: zone,
+                    body: h
+                },
+            ]
+        );
+        // in free fall all along
+        assert_approx_eq!(world.get(h).unwrap().linear_velocity().z, -10.0);
+    }
+
+    #[test]
+    fn test_sensor_body_detects_without_pushing() {
+        let mut world = World::new(Vector::zero());
+        let pickup = add_box(&mut world, 2.0);
+        let object = world.get_mut(pickup).unwrap();
+        object.set_body_type(BodyType::Static);
+        object.set_sensor(true);
+        let h = add_box(&mut world, 0.0);
+        let velocity = Vector::new(1.0, 0.0, 0.0);
+        world.get_mut(h).unwrap().set_linear_velocity(&velocity);
+        let entered = SensorEvent::Enter {
+            sensor: Collider::Body(pickup),
+            body: h,
+        };
+        let mut entered_at = None;
+        for i in 0..150 {
+            world.step(0.0, 0.01);
+            if world.sensor_events().contains(&entered) {
+                entered_at = Some(i);
+            }
+        }
+        // the boxes touch once they are one apart
+        assert!(entered_at.is_some_and(|i| (99..=101).contains(&i)));
+        assert_approx_eq!(world.get(h).unwrap().linear_velocity(), &velocity);
+        world.remove(h);
+        world.step(0.0, 0.01);
+        let exited = SensorEvent::Exit {
+            sensor: Collider::Body(pickup),
+            body: h,
+        };
+        assert_eq!(world.sensor_events(), [exited]);
+    }
+
+    #[test]
+    fn test_advance_keeps_sensor_events_of_every_substep() {
+        let mut world = World::new(Vector::zero());
+        world.add_static(StaticCollider::plane(&Vector::new(0.0, 0.0, 1.0), 0.0).as_sensor());
+        add_box_at_height(&mut world, 0.4);
+        world.set_fixed_timestep(0.01);
+        world.advance(0.035);
+        assert_eq!(world.sensor_events().len(), 1);
+        world.advance(0.01);
+        assert!(world.sensor_events().is_empty());
+    }
+
+    #[test]
+    fn test_contact_events_of_landing_box() {
+        let mut world = World::new(Vector::new(0.0, 0.0, -10.0));
+        let ground = world.add_static(StaticCollider::plane(&Vector::new(0.0, 0.0, 1.0), 0.0));
+        world.set_solver_config(SolverConfig {
+            restitution: 0.0,
+            ..SolverConfig::default()
+        });
+        let h = add_box_at_height(&mut world, 1.0);
+        settle(&mut world, 50);
+        let events: Vec<ContactEvent> = world.drain_contact_events().collect();
+        let ContactEvent::Started(report) = &events[0] else {
+            panic!("{:?}", events[0]);
+        };
+        assert_eq!(
+            (report.a, report.b),
+            (Collider::Static(ground), Collider::Body(h))
+        );
+        // stopped a box of mass 2 falling at about 3
+        assert!(report.impulse.z > 5.0, "impulse = {:?}", report.impulse);
+        assert!(report.point.z.abs() < 0.05, "point = {:?}", report.point);
+        assert_approx_eq!(report.normal, Vector::new(0.0, 0.0, 1.0));
+        assert!(events[1..]
+            .iter()
+            .all(|event| matches!(event, ContactEvent::Persisted(_))));
+        assert!(world.drain_contact_events().next().is_none());
+        // quiet once asleep
+        settle(&mut world, 200);
+        world.drain_contact_events();
+        settle(&mut world, 10);
+        assert!(world.drain_contact_events().next().is_none());
+        world.apply_impulse(
+            h,
+            &Vector::new(0.0, 0.0, 20.0),
+            &world.get(h).unwrap().position().clone(),
+        );
+        settle(&mut world, 10);
+        let events: Vec<ContactEvent> = world.drain_contact_events().collect();
+        assert!(matches!(
+            events.last(),
+            Some(ContactEvent::Ended {
+                a: Collider::Static(_),
+                b: Collider::Body(_)
+            })
+        ));
+    }
+
+    #[test]
+    fn test_contact_impulse_matches_momentum_change() {
+        let mut world = World::new(Vector::zero());
+        let a = add_box(&mut world, 0.0);
+        let b = add_box(&mut world, 1.05);
+        world
+            .get_mut(a)
+            .unwrap()
+            .set_linear_velocity(&Vector::new(10.0, 0.0, 0.0));
+        world.step(0.0, 0.01);
+        let events: Vec<ContactEvent> = world.drain_contact_events().collect();
+        let [ContactEvent::Started(report)] = &events[..] else {
+            panic!("{:?}", events);
+        };
+        assert_eq!((report.a, report.b), (Collider::Body(a), Collider::Body(b)));
+        let momentum = world.get(b).unwrap().linear_velocity() * 2.0;
+        assert_approx_eq!(report.impulse, momentum);
+        world.remove(b);
+        world.step(0.0, 0.01);
+        let events: Vec<ContactEvent> = world.drain_contact_events().collect();
+        assert!(matches!(
+            events[..],
+            [ContactEvent::Ended {
+                a: Collider::Body(_),
+                b: Collider::Body(_)
+            }]
+        ));
+    }
+
+    // A 10 cm box at 100 m/s, moving 5 m per step, thrown at a 5 cm plate
+    fn fire_at_plate(ccd: bool) -> f64 {
+        let mut world = World::new(Vector::zero());
+        world.add_static(StaticCollider::cuboid(
+            0.05,
+            4.0,
+            4.0,
+            &Vector::new(3.0, 0.0, 0.0),
+            &Quaternion::coords(0.0, 0.0, 0.0, 1.0),
+        ));
+        let h = world.add(
+            &RigidBox::new(0.1, 0.1, 0.1, 1.0),
+            &Vector::zero(),
+            &Quaternion::coords(0.0, 0.0, 0.0, 1.0),
+            &Vector::new(100.0, 0.0, 0.0),
+            &Vector::zero(),
+        );
+        world.get_mut(h).unwrap().set_ccd_enabled(ccd);
+        for _ in 0..10 {
+            world.step(0.0, 0.05);
+        }
+        world.get(h).unwrap().position().x
+    }
+
+    #[test]
+    fn test_ccd_stops_fast_box_at_thin_plate() {
+        assert!(fire_at_plate(false) > 3.0);
+        let x = fire_at_plate(true);
+        assert!(x < 2.975, "x = {}", x);
+    }
+
+    #[test]
+    fn test_ccd_leaves_sliding_box_alone() {
+        let slide = |ccd: bool| {
+            let mut world = World::new(Vector::new(0.0, 0.0, -10.0));
+            world.add_static(StaticCollider::plane(&Vector::new(0.0, 0.0, 1.0), 0.0));
+            let h = add_box_at_height(&mut world, 0.5);
+            let object = world.get_mut(h).unwrap();
+            object.set_ccd_enabled(ccd);
+            object.set_linear_velocity(&Vector::new(5.0, 0.0, 0.0));
+            settle(&mut world, 50);
+            world.get(h).unwrap().position().x
+        };
+        assert_approx_eq!(slide(true), slide(false));
+    }
 }